@@ -0,0 +1,15 @@
+// Brute-force noun/verb search dominated by Intcode execution speed
+use aoc_2019::day02::Day02;
+use aoc_2019::Solution;
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::fs;
+
+fn bench_day02(c: &mut Criterion) {
+    let program = fs::read_to_string("data/input02.txt").unwrap();
+
+    c.bench_function("day02 part1", |b| b.iter(|| Day02.part1(&program).unwrap()));
+    c.bench_function("day02 part2", |b| b.iter(|| Day02.part2(&program).unwrap()));
+}
+
+criterion_group!(benches, bench_day02);
+criterion_main!(benches);
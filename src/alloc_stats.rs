@@ -0,0 +1,112 @@
+// Global allocator wrapper tracking heap usage of the whole process.
+// Binary installs it when built with `alloc-stats` feature.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            if new_size > layout.size() {
+                grow(new_size - layout.size());
+            } else {
+                ALLOCATED.fetch_sub(layout.size() - new_size, Ordering::Relaxed);
+            }
+        }
+        new_ptr
+    }
+}
+
+fn grow(size: usize) {
+    let allocated = ALLOCATED.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK.fetch_max(allocated, Ordering::Relaxed);
+}
+
+// Bytes currently allocated on the heap
+pub fn allocated() -> usize {
+    ALLOCATED.load(Ordering::Relaxed)
+}
+
+// Largest number of bytes allocated at once since the last reset
+pub fn peak() -> usize {
+    PEAK.load(Ordering::Relaxed)
+}
+
+// Starts measuring peak from current usage, call it before the measured code
+pub fn reset_peak() {
+    PEAK.store(allocated(), Ordering::Relaxed);
+}
+
+// Returns heap usage peak is measured from
+pub fn start_heap_measurement() -> usize {
+    reset_peak();
+    allocated()
+}
+
+// Heap used on top of baseline since start_heap_measurement
+pub fn peak_heap(baseline: usize) -> usize {
+    peak().saturating_sub(baseline)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counting_allocator() {
+        // Allocator isn't installed in tests, so only these calls are counted
+        let layout = Layout::from_size_align(1024, 8).unwrap();
+        reset_peak();
+        let before = allocated();
+        unsafe {
+            let ptr = CountingAllocator.alloc(layout);
+            assert_eq!(allocated(), before + 1024);
+
+            let ptr = CountingAllocator.realloc(ptr, layout, 4096);
+            assert_eq!(allocated(), before + 4096);
+            let layout = Layout::from_size_align(4096, 8).unwrap();
+            let ptr = CountingAllocator.realloc(ptr, layout, 512);
+            assert_eq!(allocated(), before + 512);
+
+            let layout = Layout::from_size_align(512, 8).unwrap();
+            CountingAllocator.dealloc(ptr, layout);
+        }
+
+        assert_eq!(allocated(), before);
+        assert_eq!(peak(), before + 4096);
+
+        let baseline = start_heap_measurement();
+        assert_eq!(peak_heap(baseline), 0);
+        unsafe {
+            let ptr = CountingAllocator.alloc(layout);
+            CountingAllocator.dealloc(ptr, layout);
+        }
+        assert_eq!(peak_heap(baseline), 1024);
+    }
+}
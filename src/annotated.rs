@@ -0,0 +1,254 @@
+// Annotated Intcode program format. Besides plain comma separated values it allows
+// comments, labels and any number of values per line:
+//
+// # Adds two numbers
+// 1, x, y, sum   # add
+// 99
+// x: 5
+// y: 7
+// sum: 0
+//
+// Label is replaced by address of the value following it. Plain puzzle input
+// is valid annotated program as well.
+use crate::parse::{self, ParseError};
+use std::collections::HashMap;
+
+enum Value<'a> {
+    Number(i64),
+    // Label reference with line and column it's used at
+    Label(&'a str, usize, usize),
+}
+
+// Strips comments and resolves labels, returns program values
+pub fn parse(text: &str) -> Result<Vec<i64>, ParseError> {
+    let mut labels = HashMap::new();
+    let mut values = Vec::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let line_no = i + 1;
+        let code = line.split('#').next().unwrap_or_default();
+        if code.trim().is_empty() {
+            continue;
+        }
+
+        // Every comma separated field has a value, whitespace separates more
+        // values and label definitions within it
+        for (field_column, field) in parse::tokens(code, |c| c == ',') {
+            if field.is_empty() {
+                return Err(ParseError::new(line_no, field_column, "missing value"));
+            }
+            let tokens = parse::tokens(field, char::is_whitespace);
+            for (offset, token) in tokens.filter(|(_, t)| !t.is_empty()) {
+                let column = field_column + offset - 1;
+                if let Some(label) = token.strip_suffix(':') {
+                    if !is_label(label) {
+                        let message = format!("invalid label name {:?}", label);
+                        return Err(ParseError::new(line_no, column, message));
+                    }
+                    if labels.insert(label, values.len()).is_some() {
+                        let message = format!("label {:?} is already defined", label);
+                        return Err(ParseError::new(line_no, column, message));
+                    }
+                } else if let Ok(number) = token.parse() {
+                    values.push(Value::Number(number));
+                } else if is_label(token) {
+                    values.push(Value::Label(token, line_no, column));
+                } else {
+                    let message = format!("{:?} is not a number or label name", token);
+                    return Err(ParseError::new(line_no, column, message));
+                }
+            }
+        }
+    }
+
+    values
+        .into_iter()
+        .map(|value| match value {
+            Value::Number(number) => Ok(number),
+            Value::Label(label, line_no, column) => labels
+                .get(label)
+                .map(|&address| address as i64)
+                .ok_or_else(|| {
+                    let message = format!("undefined label {:?}", label);
+                    ParseError::new(line_no, column, message)
+                }),
+        })
+        .collect()
+}
+
+fn is_label(label: &str) -> bool {
+    let mut chars = label.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// Emits one instruction per line with its address and meaning in comment.
+// Values which don't decode as instructions are written one per line.
+pub fn format(program: &[i64]) -> String {
+    let mut out = String::new();
+    let mut address = 0;
+
+    while address < program.len() {
+        let (len, comment) = decode(|a| program.get(a).copied(), address);
+
+        let values: Vec<String> = program[address..address + len]
+            .iter()
+            .map(i64::to_string)
+            .collect();
+        out.push_str(&format!(
+            "{:<24} # {}: {}\n",
+            values.join(", "),
+            address,
+            comment
+        ));
+        address += len;
+    }
+
+    out
+}
+
+// Describes instruction at address, returns its length and meaning. Parameters in
+// position mode are shown in brackets, relative ones as offsets of relative base.
+// Cells which don't form a complete instruction with valid modes are data.
+pub(crate) fn decode(read: impl Fn(usize) -> Option<i64>, address: usize) -> (usize, String) {
+    let data = (1, "data".to_string());
+    let Some(instruction) = read(address) else {
+        return data;
+    };
+
+    // Name, number of parameters and whether the last one is written to
+    let (name, count, writes) = match instruction % 100 {
+        1 => ("add", 3, true),
+        2 => ("mul", 3, true),
+        3 => ("in", 1, true),
+        4 => ("out", 1, false),
+        5 => ("jnz", 2, false),
+        6 => ("jz", 2, false),
+        7 => ("lt", 3, true),
+        8 => ("eq", 3, true),
+        9 => ("arb", 1, false),
+        99 => ("halt", 0, false),
+        _ => return data,
+    };
+
+    let mut text = name.to_string();
+    let mut modes = instruction / 100;
+    for n in 1..=count {
+        let Some(param) = read(address + n) else {
+            return data;
+        };
+        let written = writes && n == count;
+        let operand = match modes % 10 {
+            0 => format!("[{}]", param),
+            1 if !written => param.to_string(),
+            2 => format!("[rb{:+}]", param),
+            _ => return data,
+        };
+        if written {
+            text.push_str(" ->");
+        }
+        text.push(' ');
+        text.push_str(&operand);
+        modes /= 10;
+    }
+    if modes != 0 {
+        return data;
+    }
+
+    (count + 1, text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("1,9,10,3,2,3,11,0,99,30,40,50").unwrap().len(), 12);
+        assert_eq!(parse(" 1,0,0,0,99\n").unwrap(), [1, 0, 0, 0, 99]);
+
+        let program = "\
+# Adds two numbers
+start:
+1, x, y, sum   # add
+99
+x: 5
+y: -7
+sum: 0 start
+";
+        assert_eq!(parse(program).unwrap(), [1, 5, 6, 7, 99, 5, -7, 0, 0]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = parse("1, 0, 0, missing\n99").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 1, column 10: undefined label \"missing\""
+        );
+
+        let err = parse("x: 1\n  x: 2").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 2, column 3: label \"x\" is already defined"
+        );
+
+        let err = parse("1,0,0,3,99\n1, 2x, 3").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 2, column 4: \"2x\" is not a number or label name"
+        );
+        for (program, column) in [("1,,2", 3), ("1,2,", 5), (",1", 1), ("1, 2 ,\n3", 7)] {
+            let err = parse(program).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!("line 1, column {}: missing value", column),
+                "program {:?}",
+                program
+            );
+        }
+        let err = parse("9x: 1").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 1, column 1: invalid label name \"9x\""
+        );
+    }
+
+    #[test]
+    fn test_format() {
+        let program = [1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50];
+        let text = format(&program);
+        assert_eq!(
+            text.lines().next().unwrap(),
+            "1, 9, 10, 3              # 0: add [9] [10] -> [3]"
+        );
+        assert_eq!(text.lines().count(), 6);
+        assert_eq!(parse(&text).unwrap(), program);
+
+        // Truncated instruction is kept as data
+        assert_eq!(parse(&format(&[1, 2])).unwrap(), [1, 2]);
+    }
+
+    #[test]
+    fn test_decode() {
+        let cases: [(&[i64], (usize, &str)); 9] = [
+            (&[1002, 4, 3, 4], (4, "mul [4] 3 -> [4]")),
+            (&[3, 9], (2, "in -> [9]")),
+            (&[104, -5], (2, "out -5")),
+            (&[1105, 1, 9], (3, "jnz 1 9")),
+            (&[1108, 7, 8, 3], (4, "eq 7 8 -> [3]")),
+            (&[109, 19], (2, "arb 19")),
+            (&[21201, -1, 5, 3], (4, "add [rb-1] 5 -> [rb+3]")),
+            // Written parameter in immediate mode and unknown mode
+            (&[11101, 1, 1, 4], (1, "data")),
+            (&[304, 1], (1, "data")),
+        ];
+
+        for (program, (len, text)) in cases {
+            let decoded = decode(|a| program.get(a).copied(), 0);
+            assert_eq!(decoded, (len, text.to_string()), "program {:?}", program);
+        }
+    }
+}
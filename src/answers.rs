@@ -0,0 +1,147 @@
+// Accepted answers recorded in a TOML file, one table per day:
+//
+// [day01]
+// part1 = "3576689"
+// part2 = 5362136
+//
+// Answers may be written as strings or integers, missing parts are allowed.
+// Days without a solution yet may be recorded too, verify reports them as missing.
+use crate::metadata::{self, Outcome};
+use anyhow::{anyhow, bail, Context, Result};
+use std::collections::BTreeMap;
+use std::fs;
+
+// File verify command reads by default
+pub const DEFAULT_PATH: &str = "answers.toml";
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Answers {
+    days: BTreeMap<u8, [Option<String>; 2]>,
+}
+
+impl Answers {
+    pub fn read(path: &str) -> Result<Answers> {
+        let content =
+            fs::read_to_string(path).with_context(|| format!("Failed to read {}", path))?;
+        Answers::parse(&content).with_context(|| format!("Invalid answers in {}", path))
+    }
+
+    pub fn parse(content: &str) -> Result<Answers> {
+        let table: toml::Table = content.parse()?;
+        let mut days = BTreeMap::new();
+        for (key, value) in &table {
+            let day = metadata::parse_day_number(key)?;
+            let parts = value
+                .as_table()
+                .ok_or_else(|| anyhow!("{} must be a table with part1 and part2", key))?;
+
+            let mut answers = [None, None];
+            for (name, value) in parts {
+                let index = match name.as_str() {
+                    "part1" => 0,
+                    "part2" => 1,
+                    _ => bail!("Unknown key {}.{}, expected part1 or part2", key, name),
+                };
+                answers[index] = Some(match value {
+                    toml::Value::String(s) => s.clone(),
+                    toml::Value::Integer(n) => n.to_string(),
+                    _ => bail!("Answer {}.{} must be a string or an integer", key, name),
+                });
+            }
+            days.insert(day, answers);
+        }
+
+        Ok(Answers { days })
+    }
+
+    // Expected answer of the part, None when it isn't recorded
+    pub fn get(&self, day: u8, part: u8) -> Option<&str> {
+        let answers = self.days.get(&day)?;
+        answers.get(part as usize - 1)?.as_deref()
+    }
+
+    // Days with at least one recorded answer
+    pub fn days(&self) -> impl Iterator<Item = u8> + '_ {
+        self.days.keys().copied()
+    }
+}
+
+// Solves both parts of the day and compares them with recorded answers,
+// parts without an answer or days without a solution are skipped
+pub fn check(day: u8, input: &str, answers: &Answers) -> [Outcome; 2] {
+    let Some(solution) = crate::solution(day) else {
+        return [Outcome::Skip, Outcome::Skip];
+    };
+
+    [
+        metadata::compare(answers.get(day, 1), || solution.part1(input)),
+        metadata::compare(answers.get(day, 2), || solution.part2(input)),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let answers =
+            Answers::parse("[day01]\npart1 = \"2\"\npart2 = 966\n\n[03]\npart2 = \"30\"").unwrap();
+        assert_eq!(answers.get(1, 1), Some("2"));
+        assert_eq!(answers.get(1, 2), Some("966"));
+        assert_eq!(answers.get(3, 1), None);
+        assert_eq!(answers.get(3, 2), Some("30"));
+        assert_eq!(answers.get(4, 1), None);
+        assert_eq!(answers.days().collect::<Vec<_>>(), [1, 3]);
+        assert_eq!(Answers::parse("").unwrap(), Answers::default());
+
+        let errors = [
+            (
+                "[day01]\npart3 = \"1\"",
+                "Unknown key day01.part3, expected part1 or part2",
+            ),
+            (
+                "[day01]\npart1 = 1.5",
+                "Answer day01.part1 must be a string or an integer",
+            ),
+            (
+                "day01 = \"1\"",
+                "day01 must be a table with part1 and part2",
+            ),
+            (
+                "[day26]\npart1 = \"1\"",
+                "Day 26 is out of range, puzzles are numbered 1-25",
+            ),
+        ];
+        for (content, message) in errors {
+            let err = Answers::parse(content).unwrap_err();
+            assert_eq!(err.to_string(), message, "content {:?}", content);
+        }
+        assert!(Answers::parse("[day01\n").is_err());
+
+        // Answers of days not solved yet are kept
+        let answers = Answers::parse("[day25]\npart1 = \"1\"").unwrap();
+        assert_eq!(answers.get(25, 1), Some("1"));
+    }
+
+    #[test]
+    fn test_check() {
+        let answers =
+            Answers::parse("[day03]\npart1 = \"6\"\npart2 = \"31\"\n[day01]\npart1 = 2").unwrap();
+        let outcomes = check(3, "R8,U5,L5,D3\nU7,R6,D4,L4", &answers);
+        assert_eq!(outcomes[0], Outcome::Pass);
+        assert_eq!(
+            outcomes[1],
+            Outcome::Fail {
+                expected: "31".to_string(),
+                actual: "30".to_string(),
+            }
+        );
+
+        assert_eq!(check(1, "12", &answers), [Outcome::Pass, Outcome::Skip]);
+        assert!(matches!(check(3, "R8", &answers)[0], Outcome::Error(_)));
+
+        let answers = Answers::parse("[day25]\npart1 = \"1\"").unwrap();
+        assert_eq!(check(25, "", &answers), [Outcome::Skip, Outcome::Skip]);
+    }
+}
@@ -0,0 +1,150 @@
+// Cooperative cancellation of running solvers
+use crate::progress::Progress;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// Error returned by solvers which stopped because they were cancelled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "solving was cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+// Error returned by solvers which didn't finish before deadline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimedOut(pub Duration);
+
+impl fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "solving timed out after {:?}", self.0)
+    }
+}
+
+impl std::error::Error for TimedOut {}
+
+// Limits solving time. Passed to solvers as progress reporter, updates are
+// forwarded to the wrapped one:
+// solution.part2_with_progress(input, &Deadline::after(Duration::from_secs(1), &NoProgress))
+pub struct Deadline<'a> {
+    timeout: Duration,
+    at: Instant,
+    inner: &'a dyn Progress,
+}
+
+impl<'a> Deadline<'a> {
+    pub fn after(timeout: Duration, inner: &'a dyn Progress) -> Deadline<'a> {
+        Deadline {
+            timeout,
+            at: Instant::now() + timeout,
+            inner,
+        }
+    }
+}
+
+impl Progress for Deadline<'_> {
+    fn update(&self, done: u64, total: u64) {
+        self.inner.update(done, total);
+    }
+
+    fn check(&self) -> anyhow::Result<()> {
+        self.inner.check()?;
+        if Instant::now() >= self.at {
+            return Err(TimedOut(self.timeout).into());
+        }
+        Ok(())
+    }
+}
+
+// Shared flag, clones observe cancellation made through any of them
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> CancellationToken {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    pub fn check(&self) -> Result<(), Cancelled> {
+        if self.is_cancelled() {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    // Cancels token on Ctrl-C. Second Ctrl-C terminates process immediately.
+    pub fn cancel_on_ctrl_c(&self) -> anyhow::Result<()> {
+        let token = self.clone();
+        ctrlc::set_handler(move || {
+            if token.is_cancelled() {
+                std::process::exit(130);
+            }
+            token.cancel();
+        })?;
+        Ok(())
+    }
+}
+
+// Lets solvers stop on cancellation without drawing progress, like in benchmarks
+impl Progress for CancellationToken {
+    fn update(&self, _done: u64, _total: u64) {}
+
+    fn check(&self) -> anyhow::Result<()> {
+        Ok(CancellationToken::check(self)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day04::Day04;
+    use crate::progress::NoProgress;
+    use crate::Solution;
+
+    #[test]
+    fn test_cancel() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert!(token.check().is_ok());
+
+        clone.cancel();
+        assert!(token.is_cancelled());
+        assert_eq!(token.check(), Err(Cancelled));
+        let err = Progress::check(&token).unwrap_err();
+        assert!(err.is::<Cancelled>());
+    }
+
+    #[test]
+    fn test_deadline() {
+        let deadline = Deadline::after(Duration::from_secs(60), &NoProgress);
+        assert!(deadline.check().is_ok());
+
+        let deadline = Deadline::after(Duration::ZERO, &NoProgress);
+        let err = deadline.check().unwrap_err();
+        assert_eq!(err.downcast_ref(), Some(&TimedOut(Duration::ZERO)));
+    }
+
+    #[test]
+    fn test_solve_with_deadline() {
+        let deadline = Deadline::after(Duration::ZERO, &NoProgress);
+        let err = Day04
+            .part1_with_progress("100000-999999", &deadline)
+            .unwrap_err();
+        assert!(err.is::<TimedOut>());
+    }
+}
@@ -1,137 +1,718 @@
-use anyhow::{anyhow, bail, Context, Result};
+use crate::annotated;
+use crate::progress::{NoProgress, Progress};
+use anyhow::{bail, Context, Result};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::marker::PhantomData;
 
-#[derive(Debug, Default)]
-pub struct IntcodeComputer {
-    program: Vec<i32>,
-    ram: Ram,
+// Reason execution stopped, so several computers can be run in turns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepResult {
+    Halted,
+    // Input instruction found the input queue empty, it's executed again
+    // when execution resumes
+    NeedsInput,
+    Output(i64),
+}
+
+// Error which stopped the program, with instruction being executed when it happened
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fault {
+    pub address: usize,
+    // None when the instruction itself couldn't be read
+    pub instruction: Option<i64>,
+    pub kind: FaultKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FaultKind {
+    InvalidOpcode(i64),
+    InvalidMode(i64),
+    // Parameter written to is in immediate mode
+    ImmediateWrite,
+    NegativeAddress(i64),
+    Overflow,
+    RelativeBaseOverflow,
+    // Program needs input, but run() doesn't provide any
+    WaitingForInput,
+    // Memory backend failed to read or write the cell, reason is its error
+    Memory {
+        address: usize,
+        access: Access,
+        reason: String,
+    },
+}
+
+// Kind of memory access which failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+impl fmt::Display for FaultKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FaultKind::InvalidOpcode(opcode) => write!(f, "invalid opcode {}", opcode),
+            FaultKind::InvalidMode(mode) => write!(f, "invalid parameter mode {}", mode),
+            FaultKind::ImmediateWrite => write!(f, "parameter written to is in immediate mode"),
+            FaultKind::NegativeAddress(value) => write!(f, "negative address {}", value),
+            FaultKind::Overflow => write!(f, "arithmetic overflow"),
+            FaultKind::RelativeBaseOverflow => write!(f, "relative base overflow"),
+            FaultKind::WaitingForInput => write!(f, "program is waiting for input"),
+            FaultKind::Memory {
+                address,
+                access,
+                reason,
+            } => {
+                let access = match access {
+                    Access::Read => "read",
+                    Access::Write => "write",
+                };
+                write!(
+                    f,
+                    "failed to {} memory at address {}: {}",
+                    access, address, reason
+                )
+            }
+        }
+    }
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Intcode fault at address {}", self.address)?;
+        if let Some(instruction) = self.instruction {
+            write!(f, " (instruction {})", instruction)?;
+        }
+        write!(f, ": {}", self.kind)
+    }
+}
+
+impl std::error::Error for Fault {}
+
+// Memory errors of instructions are reported as faults too, with the cell accessed
+fn memory_fault(address: usize, access: Access) -> impl FnOnce(anyhow::Error) -> FaultKind {
+    move |err| FaultKind::Memory {
+        address,
+        access,
+        reason: format!("{:#}", err),
+    }
+}
+
+// Instructions executed between checks of progress reporter, programs with jumps
+// may loop forever and are stopped by its deadline or cancellation
+pub const CHECK_INTERVAL: u64 = 1 << 16;
+
+#[derive(Debug, Clone, Default)]
+pub struct IntcodeComputer<M: Memory = Ram> {
+    program: Vec<i64>,
+    ram: M,
     // Instruction pointer
     ip: usize,
+    // Added to parameters in relative mode, adjusted by opcode 9
+    relative_base: i64,
     halted: bool,
+    // Values consumed by input instructions and produced by output ones
+    input: VecDeque<i64>,
+    output: VecDeque<i64>,
 }
 
 impl IntcodeComputer {
+    // Creates computer with default dense memory
     pub fn new(program: &str) -> Result<IntcodeComputer> {
-        let program = program
-            .trim()
-            .split(',')
-            .map(|s| s.trim().parse::<i32>())
-            .collect::<Result<Vec<_>, _>>()
-            .context("Failed to read intcode program from file")?;
+        IntcodeComputer::builder(program)?.build()
+    }
+
+    pub fn builder(program: &str) -> Result<Builder> {
+        // Plain programs are annotated ones without comments and labels
+        let program =
+            annotated::parse(program).context("Failed to read intcode program from file")?;
 
+        Ok(Builder {
+            program,
+            memory: PhantomData,
+        })
+    }
+}
+
+// Configures computer before creation, e.g. selects memory backend:
+// IntcodeComputer::builder(program)?.memory::<SparseRam>().build()
+pub struct Builder<M: Memory = Ram> {
+    program: Vec<i64>,
+    memory: PhantomData<M>,
+}
+
+impl<M: Memory> Builder<M> {
+    pub fn memory<N: Memory>(self) -> Builder<N> {
+        Builder {
+            program: self.program,
+            memory: PhantomData,
+        }
+    }
+
+    pub fn build(self) -> Result<IntcodeComputer<M>> {
         Ok(IntcodeComputer {
-            program: program.clone(),
-            ram: Ram(program),
+            ram: M::from_program(&self.program)?,
+            program: self.program,
             ip: 0,
+            relative_base: 0,
             halted: false,
+            input: VecDeque::new(),
+            output: VecDeque::new(),
         })
     }
+}
 
-    pub fn ram(&self) -> &Ram {
+impl<M: Memory> IntcodeComputer<M> {
+    pub fn ram(&self) -> &M {
         &self.ram
     }
 
+    // Cells changed by execution compared to the loaded program
+    pub fn changes(&self) -> RamDiff {
+        diff_cells(&Ram(self.program.clone()), &self.ram)
+    }
+
+    // Cells which differ from memory of other computer, self is the old side
+    pub fn diff<N: Memory>(&self, other: &IntcodeComputer<N>) -> RamDiff {
+        diff_cells(&self.ram, &other.ram)
+    }
+
+    pub fn ip(&self) -> usize {
+        self.ip
+    }
+
+    pub fn relative_base(&self) -> i64 {
+        self.relative_base
+    }
+
+    // Describes instruction at instruction pointer, e.g. "mul [3] [11] -> [0]"
+    pub fn instruction(&self) -> String {
+        annotated::decode(|a| self.cell(a), self.ip).1
+    }
+
+    // Lists memory as instructions with parameter modes, one per line with its
+    // address. Cells which don't form an instruction are shown as data.
+    pub fn disassemble(&self) -> String {
+        let mut out = String::new();
+        let mut address = 0;
+        while address < self.ram.len() {
+            let (len, text) = annotated::decode(|a| self.cell(a), address);
+            if len == 1 && text == "data" {
+                let value = self.cell(address).unwrap_or_default();
+                out.push_str(&format!("{:>6}: data {}\n", address, value));
+            } else {
+                out.push_str(&format!("{:>6}: {}\n", address, text));
+            }
+            address += len;
+        }
+        out
+    }
+
+    // Line describing the next instruction and registers, for tracing execution
+    pub fn trace(&self) -> String {
+        format!(
+            "{:>6}: {:<32} rb: {}",
+            self.ip,
+            self.instruction(),
+            self.relative_base
+        )
+    }
+
+    // Memory cell within used memory, cells past the end aren't part of program
+    fn cell(&self, address: usize) -> Option<i64> {
+        (address < self.ram.len())
+            .then(|| self.ram.read(address).ok())
+            .flatten()
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.halted
+    }
+
     pub fn reset(&mut self) {
-        self.ram = Ram(self.program.clone());
+        self.ram.load(&self.program);
         self.ip = 0;
+        self.relative_base = 0;
         self.halted = false;
+        self.input.clear();
+        self.output.clear();
+    }
+
+    // Queues value read by the next input instruction
+    pub fn push_input(&mut self, value: i64) {
+        self.input.push_back(value);
+    }
+
+    // Takes the oldest value written by output instruction
+    pub fn pop_output(&mut self) -> Option<i64> {
+        self.output.pop_front()
+    }
+
+    // Takes every output value produced so far
+    pub fn take_output(&mut self) -> Vec<i64> {
+        self.output.drain(..).collect()
     }
 
     // Starts program execution in computer
     pub fn run(&mut self, noun: u32, verb: u32) -> Result<()> {
+        self.run_with_progress(noun, verb, &NoProgress)
+    }
+
+    pub fn run_with_progress(
+        &mut self,
+        noun: u32,
+        verb: u32,
+        progress: &dyn Progress,
+    ) -> Result<()> {
         // Additional input
-        self.ram.write(1, noun as i32)?;
-        self.ram.write(2, verb as i32)?;
+        self.ram.write(1, noun as i64)?;
+        self.ram.write(2, verb as i64)?;
 
-        self.execute()?;
-        Ok(())
+        match self.execute_with_progress(progress)? {
+            StepResult::NeedsInput => Err(self.fault(FaultKind::WaitingForInput).into()),
+            _ => Ok(()),
+        }
     }
 
-    // Internal instruction execution loop
-    fn execute(&mut self) -> Result<()> {
-        while !self.halted {
-            self.process_instruction()?;
+    // Executes single instruction, returns event it caused if any.
+    // Output value is returned instead of being queued, errors are Faults.
+    pub fn step(&mut self) -> Result<Option<StepResult>> {
+        if self.halted {
+            return Ok(Some(StepResult::Halted));
+        }
+        Ok(self
+            .process_instruction()
+            .map_err(|kind| self.fault(kind))?)
+    }
+
+    // Fault of instruction at instruction pointer
+    fn fault(&self, kind: FaultKind) -> Fault {
+        Fault {
+            address: self.ip,
+            instruction: self.ram.read(self.ip).ok(),
+            kind,
         }
-        Ok(())
     }
 
-    fn process_instruction(&mut self) -> Result<()> {
-        let instruction = Instruction::decode(&self.ram, self.ip)?;
-        match instruction {
-            Instruction::Add(a, b, dst) => self.ram.write(dst, a + b)?,
-            Instruction::Multiply(a, b, dst) => self.ram.write(dst, a * b)?,
-            Instruction::Halt => self.halted = true,
+    // Executes instructions until an output is produced, input is needed or
+    // the program halts. Can be called again to resume.
+    pub fn run_until_event(&mut self) -> Result<StepResult> {
+        self.run_until_event_with_progress(&NoProgress)
+    }
+
+    // Like run_until_event, stops with error of progress check
+    pub fn run_until_event_with_progress(&mut self, progress: &dyn Progress) -> Result<StepResult> {
+        for steps in 1.. {
+            if let Some(event) = self.step()? {
+                return Ok(event);
+            }
+            if steps % CHECK_INTERVAL == 0 {
+                progress.check()?;
+            }
+        }
+        unreachable!("steps are never exhausted")
+    }
+
+    // Executes instructions until the program halts or needs input,
+    // output values are queued
+    pub fn execute(&mut self) -> Result<StepResult> {
+        self.execute_with_progress(&NoProgress)
+    }
+
+    // Like execute, stops with error of progress check. Outputs count as steps,
+    // so programs printing forever are stopped too.
+    pub fn execute_with_progress(&mut self, progress: &dyn Progress) -> Result<StepResult> {
+        for steps in 1.. {
+            match self.step()? {
+                Some(StepResult::Output(value)) => self.output.push_back(value),
+                Some(event) => return Ok(event),
+                None => {}
+            }
+            if steps % CHECK_INTERVAL == 0 {
+                progress.check()?;
+            }
+        }
+        unreachable!("steps are never exhausted")
+    }
+
+    // Decodes and executes instruction in place. Operands are resolved straight
+    // from memory, so nothing is built or allocated per step.
+    fn process_instruction(&mut self) -> Result<Option<StepResult>, FaultKind> {
+        let ip = self.ip;
+        let instruction = self.ram.read(ip).map_err(memory_fault(ip, Access::Read))?;
+        let opcode = instruction % 100;
+        match opcode {
+            // Opcode 1 adds together numbers read from two positions and stores the result in a third position.
+            // Opcode 2 works exactly like opcode 1, except it multiplies the two inputs instead of adding them.
+            // Opcode 7 stores 1 if the first parameter is less than the second one, opcode 8 if they are equal, 0 otherwise.
+            1 | 2 | 7 | 8 => {
+                let operand1 = self.param(instruction, 1)?;
+                let operand2 = self.param(instruction, 2)?;
+                let dest = self.dest(instruction, 3)?;
+
+                let value = match opcode {
+                    1 => operand1.checked_add(operand2),
+                    2 => operand1.checked_mul(operand2),
+                    7 => Some((operand1 < operand2) as i64),
+                    _ => Some((operand1 == operand2) as i64),
+                }
+                .ok_or(FaultKind::Overflow)?;
+                self.ram
+                    .write(dest, value)
+                    .map_err(memory_fault(dest, Access::Write))?;
+                self.ip += 4;
+            }
+            // Opcode 3 takes a single integer as input and saves it to the position given by its only parameter.
+            3 => {
+                let dest = self.dest(instruction, 1)?;
+                let Some(value) = self.input.pop_front() else {
+                    return Ok(Some(StepResult::NeedsInput));
+                };
+                self.ram
+                    .write(dest, value)
+                    .map_err(memory_fault(dest, Access::Write))?;
+                self.ip += 2;
+            }
+            // Opcode 4 outputs the value of its only parameter.
+            4 => {
+                let value = self.param(instruction, 1)?;
+                self.ip += 2;
+                return Ok(Some(StepResult::Output(value)));
+            }
+            // Opcode 5 jumps to the second parameter if the first one is non-zero, opcode 6 if it's zero.
+            5 | 6 => {
+                let condition = self.param(instruction, 1)?;
+                let target = self.param(instruction, 2)?;
+                if (condition != 0) == (opcode == 5) {
+                    self.ip = address(target)?;
+                } else {
+                    self.ip += 3;
+                }
+            }
+            // Opcode 9 adjusts the relative base by the value of its only parameter.
+            9 => {
+                let offset = self.param(instruction, 1)?;
+                self.relative_base = self
+                    .relative_base
+                    .checked_add(offset)
+                    .ok_or(FaultKind::RelativeBaseOverflow)?;
+                self.ip += 2;
+            }
+            // 99 means that the program is finished and should immediately halt.
+            // Don't move further when halt is reached.
+            99 => {
+                self.halted = true;
+                return Ok(Some(StepResult::Halted));
+            }
+            _ => return Err(FaultKind::InvalidOpcode(opcode)),
+        };
+        Ok(None)
+    }
+
+    // Reads value of n-th parameter according to its mode
+    fn param(&self, instruction: i64, n: usize) -> Result<i64, FaultKind> {
+        let address = match mode(instruction, n) {
+            1 => self.ip + n,
+            _ => self.dest(instruction, n)?,
         };
-        self.ip += instruction.size();
+        self.ram
+            .read(address)
+            .map_err(memory_fault(address, Access::Read))
+    }
+
+    // Returns address n-th parameter refers to, it must not be in immediate mode
+    fn dest(&self, instruction: i64, n: usize) -> Result<usize, FaultKind> {
+        let cell = self.ip + n;
+        let value = self
+            .ram
+            .read(cell)
+            .map_err(memory_fault(cell, Access::Read))?;
+        match mode(instruction, n) {
+            0 => address(value),
+            2 => address(self.relative_base.saturating_add(value)),
+            1 => Err(FaultKind::ImmediateWrite),
+            mode => Err(FaultKind::InvalidMode(mode)),
+        }
+    }
+}
+
+// Mode of n-th parameter, 0 is position, 1 is immediate and 2 is relative mode
+fn mode(instruction: i64, n: usize) -> i64 {
+    instruction / 10i64.pow(n as u32 + 1) % 10
+}
+
+fn address(value: i64) -> Result<usize, FaultKind> {
+    usize::try_from(value).map_err(|_| FaultKind::NegativeAddress(value))
+}
+
+// Memory cells shown around instruction pointer
+const WINDOW: usize = 8;
+
+// Shows state and memory around instruction pointer, cell it points to is in brackets:
+//
+// ip: 4, relative base: 0, halted: false
+// next: mul [3] [11] -> [0]
+//      0: 1 9 10 70
+//      4: [2] 3 11 0
+//      8: 99 30 40 50
+impl<M: Memory> fmt::Display for IntcodeComputer<M> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "ip: {}, relative base: {}, halted: {}",
+            self.ip, self.relative_base, self.halted
+        )?;
+        writeln!(f, "next: {}", self.instruction())?;
+
+        // Rows of 4 cells, aligned to multiples of 4
+        let start = self.ip.saturating_sub(WINDOW) / 4 * 4;
+        let end = (self.ip + WINDOW).min(self.ram.len());
+        for row in (start..end).step_by(4) {
+            let cells: Vec<String> = (row..(row + 4).min(end))
+                .map(|address| {
+                    let value = self
+                        .ram
+                        .read(address)
+                        .map_or("?".to_string(), |v| v.to_string());
+                    if address == self.ip {
+                        format!("[{}]", value)
+                    } else {
+                        value
+                    }
+                })
+                .collect();
+            writeln!(f, "{:>6}: {}", row, cells.join(" "))?;
+        }
         Ok(())
     }
 }
 
-#[derive(Debug, Default, PartialEq)]
-pub struct Ram(Vec<i32>);
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellChange {
+    pub address: usize,
+    // None when the cell is past the end of memory
+    pub old: Option<i64>,
+    pub new: Option<i64>,
+}
+
+// Changed memory cells ordered by address
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RamDiff(pub Vec<CellChange>);
 
-impl Ram {
-    pub fn read(&self, address: usize) -> Result<&i32> {
-        self.0.get(address).ok_or(anyhow!(
-            "Read RAM failure: out of bounds access, address {}",
-            address
-        ))
-    }
-
-    fn write(&mut self, address: usize, value: i32) -> Result<()> {
-        let v = self.0.get_mut(address).ok_or(anyhow!(
-            "Write RAM failure: out of bounds access, address {}",
-            address
-        ))?;
-        *v = value;
+impl RamDiff {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Display for RamDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let cell = |value: Option<i64>| value.map_or("-".to_string(), |v| v.to_string());
+        for change in &self.0 {
+            writeln!(
+                f,
+                "{:>6}: {} -> {}",
+                change.address,
+                cell(change.old),
+                cell(change.new)
+            )?;
+        }
         Ok(())
     }
 }
 
-enum Instruction {
-    Add(i32, i32, usize),      // operand 1, operand 2, destination
-    Multiply(i32, i32, usize), // operand 1, operand 2, destination
-    Halt,
+fn diff_cells(old: &dyn Memory, new: &dyn Memory) -> RamDiff {
+    // Cells past the end of memory read as zero, but are shown as missing
+    let cell = |ram: &dyn Memory, address| {
+        (address < ram.len())
+            .then(|| ram.read(address).ok())
+            .flatten()
+    };
+    let changes = (0..old.len().max(new.len()))
+        .filter_map(|address| {
+            let (old, new) = (cell(old, address), cell(new, address));
+            (old != new).then_some(CellChange { address, old, new })
+        })
+        .collect();
+    RamDiff(changes)
 }
 
-impl Instruction {
-    fn decode(mem: &Ram, address: usize) -> Result<Instruction> {
-        let &opcode = mem.read(address)?;
-        match opcode {
-            // Opcode 1 adds together numbers read from two positions and stores the result in a third position.
-            // Opcode 2 works exactly like opcode 1, except it multiplies the two inputs instead of adding them.
-            1 | 2 => {
-                let &operand1 = mem.read(address + 1)?;
-                let &operand2 = mem.read(address + 2)?;
-                let &dest = mem.read(address + 3)?;
+// Storage of computer memory cells. Backends differ in access patterns they are good at.
+pub trait Memory {
+    fn from_program(program: &[i64]) -> Result<Self>
+    where
+        Self: Sized;
 
-                // Read actual values for operation
-                let &operand1 = mem.read(operand1 as usize)?;
-                let &operand2 = mem.read(operand2 as usize)?;
+    // Restores memory to initial program state
+    fn load(&mut self, program: &[i64]);
 
-                if opcode == 1 {
-                    Ok(Self::Add(operand1, operand2, dest as usize))
-                } else {
-                    Ok(Self::Multiply(operand1, operand2, dest as usize))
-                }
+    fn read(&self, address: usize) -> Result<i64>;
+    fn write(&mut self, address: usize, value: i64) -> Result<()>;
+
+    // Returns number of cells in use
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// Dense memory growing on writes past its end, cells past the end read as zero.
+// Growth is limited to MAX_CELLS, SparseRam suits programs using far addresses.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Ram(Vec<i64>);
+
+impl Ram {
+    pub const MAX_CELLS: usize = 1 << 24;
+}
+
+impl Memory for Ram {
+    fn from_program(program: &[i64]) -> Result<Ram> {
+        Ok(Ram(program.to_vec()))
+    }
+
+    fn load(&mut self, program: &[i64]) {
+        // Reuses memory buffer instead of allocating new one
+        self.0.clear();
+        self.0.extend_from_slice(program);
+    }
+
+    fn read(&self, address: usize) -> Result<i64> {
+        Ok(self.0.get(address).copied().unwrap_or(0))
+    }
+
+    fn write(&mut self, address: usize, value: i64) -> Result<()> {
+        if address >= self.0.len() {
+            let limit = Ram::MAX_CELLS.max(self.0.len());
+            if address >= limit {
+                bail!("RAM is limited to {} cells", limit);
             }
-            // 99 means that the program is finished and should immediately halt.
-            99 => Ok(Self::Halt),
-            _ => bail!("Invalid opcode encountered: {} at {}", opcode, address),
+            self.0.resize(address + 1, 0);
         }
+        self.0[address] = value;
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+// Sparse memory for programs touching few far away addresses.
+// Any address can be written, cells which weren't written read as zero.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SparseRam {
+    cells: HashMap<usize, i64>,
+    len: usize,
+}
+
+impl Memory for SparseRam {
+    fn from_program(program: &[i64]) -> Result<SparseRam> {
+        let mut ram = SparseRam::default();
+        ram.load(program);
+        Ok(ram)
+    }
+
+    fn load(&mut self, program: &[i64]) {
+        self.cells.clear();
+        self.cells.extend(
+            program
+                .iter()
+                .enumerate()
+                .filter(|(_, &v)| v != 0)
+                .map(|(a, &v)| (a, v)),
+        );
+        self.len = program.len();
+    }
+
+    fn read(&self, address: usize) -> Result<i64> {
+        Ok(self.cells.get(&address).copied().unwrap_or(0))
+    }
+
+    fn write(&mut self, address: usize, value: i64) -> Result<()> {
+        self.cells.insert(address, value);
+        self.len = self.len.max(address + 1);
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+// Fixed size anonymous memory mapping. Pages are committed by OS on first access,
+// so large address space is cheap as long as only part of it is used.
+#[cfg(feature = "mmap")]
+const CELL_BYTES: usize = std::mem::size_of::<i64>();
+
+#[cfg(feature = "mmap")]
+#[derive(Debug)]
+pub struct MappedRam {
+    map: memmap2::MmapMut,
+    // Cells after this one are known to be zero
+    len: usize,
+}
+
+#[cfg(feature = "mmap")]
+impl MappedRam {
+    // Number of cells available beside program
+    pub const CELLS: usize = 1 << 24;
+
+    fn cell(&self, address: usize) -> Option<std::ops::Range<usize>> {
+        let start = address.checked_mul(CELL_BYTES)?;
+        let end = start.checked_add(CELL_BYTES)?;
+        (end <= self.map.len()).then_some(start..end)
+    }
+
+    fn bounds_error(&self) -> anyhow::Error {
+        anyhow::anyhow!("only {} cells are mapped", self.map.len() / CELL_BYTES)
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl Memory for MappedRam {
+    fn from_program(program: &[i64]) -> Result<MappedRam> {
+        let cells = MappedRam::CELLS.max(program.len());
+        let map = memmap2::MmapMut::map_anon(cells * CELL_BYTES).context("Failed to map RAM")?;
+        let mut ram = MappedRam { map, len: 0 };
+        ram.load(program);
+        Ok(ram)
     }
 
-    // Returns size of instruction
-    fn size(&self) -> usize {
-        match *self {
-            Self::Add(_, _, _) | Self::Multiply(_, _, _) => 4,
-            Self::Halt => 0, // Don't move further when halt is reached
+    fn load(&mut self, program: &[i64]) {
+        self.map[..self.len * CELL_BYTES].fill(0);
+        for (cell, value) in self.map.chunks_exact_mut(CELL_BYTES).zip(program) {
+            cell.copy_from_slice(&value.to_ne_bytes());
         }
+        self.len = program.len();
+    }
+
+    fn read(&self, address: usize) -> Result<i64> {
+        let cell = self.cell(address).ok_or_else(|| self.bounds_error())?;
+        let bytes: [u8; CELL_BYTES] = self.map[cell].try_into()?;
+        Ok(i64::from_ne_bytes(bytes))
+    }
+
+    fn write(&mut self, address: usize, value: i64) -> Result<()> {
+        let cell = self.cell(address).ok_or_else(|| self.bounds_error())?;
+        self.map[cell].copy_from_slice(&value.to_ne_bytes());
+        self.len = self.len.max(address + 1);
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.len
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cancel::{CancellationToken, Cancelled, Deadline, TimedOut};
+    use crate::progress::ProgressBar;
+    use std::time::Duration;
 
     #[test]
     fn test_new() {
@@ -144,7 +725,7 @@ mod tests {
         ];
 
         for (input, expected) in cases {
-            let c = IntcodeComputer::new(&input);
+            let c = IntcodeComputer::new(input);
             assert!(c.is_ok());
             assert_eq!(c.unwrap().ram.0, expected);
         }
@@ -189,6 +770,92 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_step() {
+        let mut c = IntcodeComputer::new("1, 0, 0, 0, 99").unwrap();
+        assert_eq!(c.step().unwrap(), None);
+        assert_eq!(c.ip(), 4);
+        assert!(!c.is_halted());
+        assert_eq!(c.step().unwrap(), Some(StepResult::Halted));
+        assert!(c.is_halted());
+        assert_eq!(c.step().unwrap(), Some(StepResult::Halted));
+        assert_eq!(c.ip(), 4);
+    }
+
+    #[test]
+    fn test_display() {
+        let mut c = IntcodeComputer::new("1,9,10,3,2,3,11,0,99,30,40,50").unwrap();
+        c.step().unwrap();
+        assert_eq!(
+            c.to_string(),
+            "\
+ip: 4, relative base: 0, halted: false
+next: mul [3] [11] -> [0]
+     0: 1 9 10 70
+     4: [2] 3 11 0
+     8: 99 30 40 50
+"
+        );
+
+        c.step().unwrap();
+        c.step().unwrap();
+        assert!(c
+            .to_string()
+            .starts_with("ip: 8, relative base: 0, halted: true\nnext: halt\n     0: 3500"));
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let mut c = IntcodeComputer::new("109,19,1002,4,3,4,204,-1,99,7").unwrap();
+        assert_eq!(
+            c.disassemble(),
+            "     0: arb 19\n     2: mul [4] 3 -> [4]\n     6: out [rb-1]\n     8: halt\n     9: data 7\n"
+        );
+
+        assert_eq!(c.trace(), "     0: arb 19                           rb: 0");
+        c.step().unwrap();
+        assert_eq!(c.trace(), "     2: mul [4] 3 -> [4]                 rb: 19");
+    }
+
+    #[test]
+    fn test_diff() {
+        let mut c = IntcodeComputer::new("1,9,10,3,2,3,11,0,99,30,40,50").unwrap();
+        assert!(c.changes().is_empty());
+        c.run(9, 10).unwrap();
+        assert_eq!(
+            c.changes().to_string(),
+            "     0: 1 -> 3500\n     3: 3 -> 70\n"
+        );
+
+        let mut other = IntcodeComputer::new("1,9,10,3,2,3,11,0,99,30,40,50,7").unwrap();
+        other.run(9, 11).unwrap();
+        assert_eq!(
+            c.diff(&other).0,
+            [
+                CellChange {
+                    address: 0,
+                    old: Some(3500),
+                    new: Some(4000)
+                },
+                CellChange {
+                    address: 2,
+                    old: Some(10),
+                    new: Some(11)
+                },
+                CellChange {
+                    address: 3,
+                    old: Some(70),
+                    new: Some(80)
+                },
+                CellChange {
+                    address: 12,
+                    old: None,
+                    new: Some(7)
+                },
+            ]
+        );
+    }
+
     #[test]
     fn test_opcode_halt() {
         let mut c = IntcodeComputer::new("99").unwrap();
@@ -228,12 +895,282 @@ mod tests {
         assert_eq!(c.ram.0[0], 10 * 11);
     }
 
+    #[test]
+    fn test_sparse_memory() {
+        let mut c = IntcodeComputer::builder("1, 0, 0, 100000, 99")
+            .unwrap()
+            .memory::<SparseRam>()
+            .build()
+            .unwrap();
+        assert!(c.execute().is_ok());
+        assert_eq!(c.ram().read(100000).unwrap(), 2);
+        assert_eq!(c.ram().read(50).unwrap(), 0);
+        assert_eq!(c.ram().len(), 100001);
+
+        c.reset();
+        assert_eq!(c.ram().read(100000).unwrap(), 0);
+        assert_eq!(c.ram().len(), 5);
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_mapped_memory() {
+        let mut c = IntcodeComputer::builder("2, 4, 4, 5, 99, 0")
+            .unwrap()
+            .memory::<MappedRam>()
+            .build()
+            .unwrap();
+        assert!(c.execute().is_ok());
+        assert_eq!(c.ram().read(5).unwrap(), 9801);
+        assert!(c.ram.write(1000, -7).is_ok());
+        assert_eq!(c.ram().read(1000).unwrap(), -7);
+        let err = c.ram().read(MappedRam::CELLS).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("only {} cells are mapped", MappedRam::CELLS)
+        );
+        // Byte offsets of these cells don't fit into usize
+        for address in [usize::MAX / CELL_BYTES, usize::MAX] {
+            assert!(c.ram().read(address).is_err());
+            assert!(c.ram.write(address, 1).is_err());
+        }
+
+        c.reset();
+        assert_eq!(c.ram().read(5).unwrap(), 0);
+        assert_eq!(c.ram().read(1000).unwrap(), 0);
+    }
+
+    // Runs program with single input value, returns its output
+    fn run_with_input(program: &str, input: i64) -> Vec<i64> {
+        let mut c = IntcodeComputer::new(program).unwrap();
+        c.push_input(input);
+        c.execute().unwrap();
+        c.take_output()
+    }
+
+    #[test]
+    fn test_io() {
+        let mut c = IntcodeComputer::new("3,0,4,0,99").unwrap();
+        c.push_input(42);
+        assert_eq!(c.execute().unwrap(), StepResult::Halted);
+        assert_eq!(c.pop_output(), Some(42));
+        assert_eq!(c.pop_output(), None);
+
+        c.reset();
+        assert_eq!(c.execute().unwrap(), StepResult::NeedsInput);
+        assert_eq!(c.ip(), 0);
+        c.push_input(7);
+        assert_eq!(c.run_until_event().unwrap(), StepResult::Output(7));
+        assert_eq!(c.run_until_event().unwrap(), StepResult::Halted);
+        assert_eq!(c.run_until_event().unwrap(), StepResult::Halted);
+        assert_eq!(c.pop_output(), None);
+    }
+
+    #[test]
+    fn test_feedback_loop() {
+        // Amplifiers connected in a loop, each computer runs until it produces output
+        let program = "\
+3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5";
+        let mut amplifiers: Vec<IntcodeComputer> = [9, 8, 7, 6, 5]
+            .iter()
+            .map(|&phase| {
+                let mut c = IntcodeComputer::new(program).unwrap();
+                c.push_input(phase);
+                c
+            })
+            .collect();
+
+        let mut signal = 0;
+        'feedback: loop {
+            for amplifier in &mut amplifiers {
+                amplifier.push_input(signal);
+                match amplifier.run_until_event().unwrap() {
+                    StepResult::Output(value) => signal = value,
+                    StepResult::Halted => break 'feedback,
+                    StepResult::NeedsInput => panic!("amplifier is waiting for input"),
+                }
+            }
+        }
+        assert_eq!(signal, 139629729);
+    }
+
+    #[test]
+    fn test_parameter_modes() {
+        let mut c = IntcodeComputer::new("1002,4,3,4,33").unwrap();
+        assert!(c.execute().is_ok());
+        assert_eq!(c.ram.0[4], 99);
+
+        let mut c = IntcodeComputer::new("1101,100,-1,4,0").unwrap();
+        assert!(c.execute().is_ok());
+        assert_eq!(c.ram.0[4], 99);
+
+        // Parameters written to can't be in immediate mode
+        let mut c = IntcodeComputer::new("11101,1,1,4,99").unwrap();
+        assert!(c.execute().is_err());
+        let mut c = IntcodeComputer::new("201,1,1,4,99").unwrap();
+        assert!(c.execute().is_err());
+    }
+
+    #[test]
+    fn test_comparisons() {
+        // Position and immediate mode programs checking whether input equals 8 or is less than it
+        let equals = ["3,9,8,9,10,9,4,9,99,-1,8", "3,3,1108,-1,8,3,4,3,99"];
+        let less = ["3,9,7,9,10,9,4,9,99,-1,8", "3,3,1107,-1,8,3,4,3,99"];
+        for program in equals {
+            assert_eq!(run_with_input(program, 8), [1]);
+            assert_eq!(run_with_input(program, 7), [0]);
+        }
+        for program in less {
+            assert_eq!(run_with_input(program, 7), [1]);
+            assert_eq!(run_with_input(program, 8), [0]);
+        }
+    }
+
+    #[test]
+    fn test_jumps() {
+        let programs = [
+            "3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9",
+            "3,3,1105,-1,9,1101,0,0,12,4,12,99,1",
+        ];
+        for program in programs {
+            assert_eq!(run_with_input(program, 0), [0]);
+            assert_eq!(run_with_input(program, 5), [1]);
+        }
+
+        let program = "\
+3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,1002,21,125,20,4,20,1105,\
+1,46,104,999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99";
+        assert_eq!(run_with_input(program, 7), [999]);
+        assert_eq!(run_with_input(program, 8), [1000]);
+        assert_eq!(run_with_input(program, 9), [1001]);
+    }
+
+    #[test]
+    fn test_infinite_loop() {
+        let deadline = Deadline::after(Duration::ZERO, &NoProgress);
+        let mut c = IntcodeComputer::new("1105,1,0").unwrap();
+        let err = c.execute_with_progress(&deadline).unwrap_err();
+        assert!(err.is::<TimedOut>());
+        let err = c.run_until_event_with_progress(&deadline).unwrap_err();
+        assert!(err.is::<TimedOut>());
+
+        // Noun and verb make the program jump to its start
+        let mut c = IntcodeComputer::new("1105,0,3,99").unwrap();
+        assert!(c
+            .run_with_progress(1, 0, &deadline)
+            .unwrap_err()
+            .is::<TimedOut>());
+        c.reset();
+        assert!(c.run_with_progress(0, 0, &deadline).is_ok());
+
+        // Program printing forever
+        let token = CancellationToken::new();
+        token.cancel();
+        let progress = ProgressBar::new().cancellable(token);
+        let mut c = IntcodeComputer::new("104,1,1105,1,0").unwrap();
+        let err = c.execute_with_progress(&progress).unwrap_err();
+        assert!(err.is::<Cancelled>());
+        assert_eq!(c.pop_output(), Some(1));
+    }
+
     #[test]
     fn test_opcode_invalid_access() {
         let mut c = IntcodeComputer {
-            ram: Ram(vec![1, 100]),
+            ram: Ram(vec![1, -1, 0, 0]),
             ..Default::default()
         };
         assert!(c.process_instruction().is_err());
+
+        let mut c = IntcodeComputer {
+            ram: Ram(vec![1, 0, 0, Ram::MAX_CELLS as i64]),
+            ..Default::default()
+        };
+        assert!(c.process_instruction().is_err());
+    }
+
+    #[test]
+    fn test_faults() {
+        let fault = |program: &str| {
+            let mut c = IntcodeComputer::new(program).unwrap();
+            c.execute().unwrap_err().downcast::<Fault>().unwrap()
+        };
+
+        let f = fault("1,0,0,0,42,99");
+        assert_eq!((f.address, f.instruction), (4, Some(42)));
+        assert_eq!(f.kind, FaultKind::InvalidOpcode(42));
+        assert_eq!(
+            f.to_string(),
+            "Intcode fault at address 4 (instruction 42): invalid opcode 42"
+        );
+        assert_eq!(fault("11101,1,2,0,99").kind, FaultKind::ImmediateWrite);
+        assert_eq!(fault("301,1,2,0,99").kind, FaultKind::InvalidMode(3));
+        assert_eq!(fault("1,-5,0,0,99").kind, FaultKind::NegativeAddress(-5));
+        assert_eq!(fault("1105,1,7").address, 7);
+
+        let f = fault(&format!("1,0,0,{},99", Ram::MAX_CELLS));
+        assert_eq!(
+            f.kind,
+            FaultKind::Memory {
+                address: Ram::MAX_CELLS,
+                access: Access::Write,
+                reason: format!("RAM is limited to {} cells", Ram::MAX_CELLS),
+            }
+        );
+        assert_eq!(
+            f.to_string(),
+            format!(
+                "Intcode fault at address 0 (instruction 1): failed to write memory at \
+                 address {0}: RAM is limited to {0} cells",
+                Ram::MAX_CELLS
+            )
+        );
+
+        let mut c = IntcodeComputer::new("3,0,99").unwrap();
+        let f = c.run(0, 0).unwrap_err().downcast::<Fault>().unwrap();
+        assert_eq!((f.address, f.kind), (0, FaultKind::WaitingForInput));
+    }
+
+    #[test]
+    fn test_growing_memory() {
+        let mut c = IntcodeComputer::new("1, 100, 0, 10, 99").unwrap();
+        assert!(c.execute().is_ok());
+        assert_eq!(c.ram().read(10).unwrap(), 1);
+        assert_eq!(c.ram().read(1000).unwrap(), 0);
+        assert_eq!(c.ram().len(), 11);
+
+        c.reset();
+        assert_eq!(c.ram().len(), 5);
+    }
+
+    #[test]
+    fn test_relative_mode() {
+        // Quine from day 9 examples
+        let program = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
+        let mut c = IntcodeComputer::new(program).unwrap();
+        assert_eq!(c.execute().unwrap(), StepResult::Halted);
+        assert_eq!(c.relative_base(), 16);
+        let expected: Vec<i64> = program.split(',').map(|v| v.parse().unwrap()).collect();
+        assert_eq!(c.take_output(), expected);
+
+        let mut c = IntcodeComputer::new("1102,34915192,34915192,7,4,7,99,0").unwrap();
+        c.execute().unwrap();
+        assert_eq!(c.take_output(), [1219070632396864]);
+
+        let mut c = IntcodeComputer::new("104,1125899906842624,99").unwrap();
+        assert_eq!(
+            c.run_until_event().unwrap(),
+            StepResult::Output(1125899906842624)
+        );
+
+        // Relative mode write
+        let mut c = IntcodeComputer::new("109,10,21101,2,3,-3,99").unwrap();
+        c.execute().unwrap();
+        assert_eq!(c.ram().read(7).unwrap(), 5);
+    }
+
+    #[test]
+    fn test_overflow() {
+        let mut c = IntcodeComputer::new("1102,4611686018427387904,4,0,99").unwrap();
+        assert!(c.execute().is_err());
     }
 }
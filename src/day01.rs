@@ -1,30 +1,82 @@
-use anyhow::Result;
-use std::fs;
+use crate::explain::Explainer;
+use crate::parse::{self, ParseError};
+use crate::Solution;
+use anyhow::{anyhow, Context, Result};
 
-pub fn solve(path: &String) -> Result<()> {
-    let input_str = fs::read_to_string(path)?;
+pub struct Day01;
 
-    let modules: Vec<u32> = input_str
-        .lines()
-        .map(|l| l.trim().parse().unwrap())
-        .collect();
+impl Solution for Day01 {
+    fn part1(&self, input: &str) -> Result<String> {
+        let ans = checked_sum(parse_modules(input)?.into_iter().map(calc_fuel))?;
+        Ok(ans.to_string())
+    }
 
-    let ans1: u32 = modules.iter().fold(0u32, |sum, val| sum + calc_fuel(*val));
+    fn part2(&self, input: &str) -> Result<String> {
+        let ans = checked_sum(parse_modules(input)?.into_iter().map(calc_fuel_total))?;
+        Ok(ans.to_string())
+    }
+
+    fn validate(&self, input: &str) -> Result<String> {
+        let modules = parse_modules(input)?;
+        Ok(format!("{} modules", modules.len()))
+    }
+
+    fn explain(&self, input: &str, explainer: &mut dyn Explainer) -> Result<()> {
+        let modules = parse_modules(input)?;
+
+        explainer.section("Part 1");
+        for (i, &mass) in modules.iter().enumerate() {
+            let fuel = calc_fuel(mass);
+            explainer.note(
+                &format!("module {}", i + 1),
+                &format_args!("mass {}, fuel {}", mass, fuel),
+            );
+        }
+        explainer.note("total", &self.part1(input)?);
+
+        explainer.section("Part 2");
+        for (i, &mass) in modules.iter().enumerate() {
+            let fuel = calc_fuel_total(mass);
+            explainer.note(
+                &format!("module {}", i + 1),
+                &format_args!("mass {}, fuel {} including fuel for fuel", mass, fuel),
+            );
+        }
+        explainer.note("total", &self.part2(input)?);
+        Ok(())
+    }
+}
 
-    let ans2: u32 = modules
-        .iter()
-        .fold(0u32, |sum, val| sum + calc_fuel_total(*val));
+fn parse_modules(input: &str) -> Result<Vec<u64>> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, l)| {
+            let mass = l.trim();
+            mass.parse().map_err(|err| {
+                let column = parse::column(l, l.len() - l.trim_start().len());
+                let message = format!("invalid mass {:?}: {}", mass, err);
+                ParseError::new(i + 1, column, message)
+            })
+        })
+        .collect::<Result<_, _>>()
+        .context("Invalid module")
+}
 
-    println!("answer 1: {}", ans1);
-    println!("answer 2: {}", ans2);
-    Ok(())
+// Sums fuel of all modules, fails instead of wrapping around on overflow
+fn checked_sum(fuel: impl Iterator<Item = u64>) -> Result<u64> {
+    fuel.enumerate().try_fold(0u64, |sum, (i, val)| {
+        sum.checked_add(val)
+            .ok_or(anyhow!("Fuel total overflows 64 bits at module {}", i + 1))
+    })
 }
 
-fn calc_fuel(mass: u32) -> u32 {
-    return (mass / 3).saturating_sub(2);
+fn calc_fuel(mass: u64) -> u64 {
+    (mass / 3).saturating_sub(2)
 }
 
-fn calc_fuel_total(module_mass: u32) -> u32 {
+// Fuel of module can't overflow as it is always less than its mass
+fn calc_fuel_total(module_mass: u64) -> u64 {
     let mut ans = 0;
     let mut mass = module_mass;
     while mass > 0 {
@@ -32,7 +84,7 @@ fn calc_fuel_total(module_mass: u32) -> u32 {
         ans += mass;
     }
 
-    return ans + mass;
+    ans + mass
 }
 
 #[cfg(test)]
@@ -52,5 +104,26 @@ mod tests {
         assert_eq!(calc_fuel_total(14), 2);
         assert_eq!(calc_fuel_total(1969), 966);
         assert_eq!(calc_fuel_total(100756), 50346);
+        assert_eq!(calc_fuel_total(u64::MAX), 9223372036854775669);
+    }
+
+    #[test]
+    fn test_checked_sum() {
+        assert_eq!(checked_sum([2, 654, 33583].into_iter()).unwrap(), 34239);
+        assert_eq!(
+            checked_sum([u32::MAX as u64; 3].into_iter()).unwrap(),
+            3 * u32::MAX as u64
+        );
+        assert!(checked_sum([u64::MAX, 1].into_iter()).is_err());
+    }
+
+    #[test]
+    fn test_parse_modules() {
+        assert_eq!(parse_modules("12\n 14 \n1969").unwrap(), [12, 14, 1969]);
+        let err = Day01.validate("12\n  x1\n").unwrap_err();
+        assert_eq!(
+            format!("{:#}", err),
+            "Invalid module: line 2, column 3: invalid mass \"x1\": invalid digit found in string"
+        );
     }
 }
@@ -1,28 +1,100 @@
-use crate::computer::IntcodeComputer;
-use anyhow::{bail, Result};
-use std::fs;
+use crate::computer::{IntcodeComputer, Memory};
+use crate::progress::{NoProgress, Progress};
+use crate::search;
+use crate::Solution;
+use anyhow::{anyhow, Result};
 
-pub fn solve(path: &str) -> Result<()> {
-    let program = fs::read_to_string(path)?;
-    let mut computer = IntcodeComputer::new(&program)?;
+const TARGET: i64 = 19690720;
 
-    computer.run(12, 2)?;
-    let &ans = computer.ram().read(0)?;
-    println!("answer 1: {}", ans);
+pub struct Day02;
 
-    let target = 19690720u32;
+impl Solution for Day02 {
+    fn part1(&self, input: &str) -> Result<String> {
+        self.part1_with_progress(input, &NoProgress)
+    }
+
+    fn part2(&self, input: &str) -> Result<String> {
+        self.part2_with_progress(input, &NoProgress)
+    }
+
+    fn part1_with_progress(&self, input: &str, progress: &dyn Progress) -> Result<String> {
+        let mut computer = IntcodeComputer::new(input)?;
+
+        computer.run_with_progress(12, 2, progress)?;
+        let ans = computer.ram().read(0)?;
+        Ok(ans.to_string())
+    }
+
+    fn part2_with_progress(&self, input: &str, progress: &dyn Progress) -> Result<String> {
+        let (noun, verb) = search_noun_verb(input, TARGET, progress)?;
+        Ok((100 * noun + verb).to_string())
+    }
+
+    fn validate(&self, input: &str) -> Result<String> {
+        let computer = IntcodeComputer::new(input)?;
+        Ok(format!("{} values", computer.ram().len()))
+    }
+}
 
-    for noun in 1..100 {
-        for verb in 1..100 {
+// Finds noun and verb which make the program leave target value at address 0
+pub fn find_noun_verb(program: &str, target: i64) -> Result<(u32, u32)> {
+    search_noun_verb(program, target, &NoProgress)
+}
+
+fn search_noun_verb(program: &str, target: i64, progress: &dyn Progress) -> Result<(u32, u32)> {
+    let computer = IntcodeComputer::new(program)?;
+    let pairs: Vec<(u32, u32)> = (0..100)
+        .flat_map(|noun| (0..100).map(move |verb| (noun, verb)))
+        .collect();
+
+    let found = search::parallel_brute_force_with(
+        &pairs,
+        progress,
+        || computer.clone(),
+        |computer, &(noun, verb)| {
             computer.reset();
-            computer.run(noun, verb)?;
-            let &val = computer.ram().read(0)?;
-            if val as u32 == target {
-                println!("answer 2: {}", 100 * noun + verb);
-                return Ok(());
-            }
-        }
+            // Jumps make some programs loop forever
+            computer.run_with_progress(noun, verb, progress)?;
+            let output = computer.ram().read(0)?;
+            Ok((output == target).then_some((noun, verb)))
+        },
+    )?;
+
+    found.ok_or_else(|| anyhow!("No noun and verb produce {}", target))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cancel::{Deadline, TimedOut};
+    use std::time::Duration;
+
+    // Adds cells addressed by noun and verb, every cell past halt holds its address
+    fn sum_program() -> String {
+        let mut program = vec![1, 0, 0, 0, 99];
+        program.extend(5..100);
+        let values: Vec<String> = program.iter().map(i32::to_string).collect();
+        values.join(",")
     }
 
-    bail!("Answer not found!");
+    #[test]
+    fn test_find_noun_verb() {
+        let program = sum_program();
+        // Noun 2 is also the cell where verb is written
+        assert_eq!(find_noun_verb(&program, 150).unwrap(), (2, 75));
+        assert_eq!(find_noun_verb(&program, 2).unwrap(), (0, 0));
+        assert!(find_noun_verb(&program, 1000).is_err());
+        // Cells past the end of short program read as zero, so it runs with every
+        // noun and verb, but none of them produces the target
+        let err = find_noun_verb("1,0,0,0,99", 1000).unwrap_err();
+        assert_eq!(err.to_string(), "No noun and verb produce 1000");
+    }
+
+    #[test]
+    fn test_search_timeout() {
+        // Noun 1 and verb 0 make the program jump to its start forever
+        let deadline = Deadline::after(Duration::from_millis(50), &NoProgress);
+        let err = search_noun_verb("1105,0,3,99", 1, &deadline).unwrap_err();
+        assert!(err.is::<TimedOut>());
+    }
 }
@@ -1,41 +1,62 @@
-use anyhow::{anyhow, Result};
-use core::fmt;
-use std::error;
-use std::fs;
+use crate::explain::Explainer;
+use crate::geometry::{self, manhattan_distance, BoundingBox, Crossing, Line, Point};
+use crate::parse::{self, ParseError};
+use crate::render::{Frame, Renderable, Style};
+use crate::Solution;
+use anyhow::{anyhow, bail, Context, Result};
 use std::str::FromStr;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct Point {
-    x: i32,
-    y: i32,
+#[derive(Debug)]
+pub struct Wire {
+    segments: Vec<Line>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-struct Line {
-    p1: Point,
-    p2: Point,
-}
+impl Wire {
+    // Iterates over straight segments in the order wire is laid
+    pub fn segments(&self) -> impl Iterator<Item = Line> + '_ {
+        self.segments.iter().copied()
+    }
 
-impl Line {
-    fn contains(&self, pt: Point) -> bool {
-        return is_value_in_range(self.p1.x, self.p2.x, pt.x)
-            && is_value_in_range(self.p1.y, self.p2.y, pt.y);
+    // Returns total number of steps along the wire
+    pub fn length(&self) -> u64 {
+        self.segments
+            .iter()
+            .map(|line| line.distance() as u64)
+            .sum()
     }
 
-    // Returns manhattan distance between two points of line
-    fn distance(&self) -> u32 {
-        return manhattan_distance(self.p1, self.p2) as u32;
+    // Wire always starts at the central port, so it's included as well
+    pub fn bounding_box(&self) -> BoundingBox {
+        let mut bbox = BoundingBox::new(POINT_CENTER);
+        for line in &self.segments {
+            bbox.include(line.p1);
+            bbox.include(line.p2);
+        }
+
+        bbox
     }
-}
 
-#[derive(Debug)]
-struct Wire {
-    segments: Vec<Line>,
-}
+    // Returns point reached after given number of steps, None past the end of wire
+    pub fn point_at(&self, steps: u64) -> Option<Point> {
+        let mut left = steps;
+        for line in &self.segments {
+            let distance = line.distance() as u64;
+            if left <= distance {
+                return Some(line.point_at(left as u32));
+            }
+            left -= distance;
+        }
+
+        // Wire without segments is just the central port
+        if steps == 0 {
+            Some(POINT_CENTER)
+        } else {
+            None
+        }
+    }
 
-impl Wire {
     // Returns number of steps required to reach desired point
-    fn steps(&self, pt: Point) -> Option<u32> {
+    pub fn steps(&self, pt: Point) -> Option<u32> {
         let mut steps = 0u32;
         for line in &self.segments {
             if line.contains(pt) {
@@ -49,52 +70,45 @@ impl Wire {
     }
 }
 
-#[derive(Debug, Clone)]
-struct ParseWireEror;
-
-impl fmt::Display for ParseWireEror {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "failed to parse wire string")
-    }
-}
-
-impl error::Error for ParseWireEror {}
-
 impl FromStr for Wire {
-    type Err = ParseWireEror;
+    type Err = ParseError;
 
+    // Errors are reported on line 1, parse_wires sets the real line
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        let items: Vec<&str> = s.split(',').map(|s| s.trim()).collect();
         let mut p1 = Point { x: 0, y: 0 };
         let mut segments = Vec::new();
-        for item in items {
-            if item.chars().count() < 2 {
-                return Err(ParseWireEror);
-            }
-
-            let (dir, count) = item.split_at(1);
-
-            let count = count.parse::<i32>().or(Err(ParseWireEror))?;
-
-            let p2 = match dir.chars().nth(0).unwrap() {
-                'R' => Ok(Point {
+        for (column, item) in parse::tokens(s, |c| c == ',') {
+            let mut chars = item.chars();
+            let dir = chars
+                .next()
+                .ok_or_else(|| ParseError::new(1, column, "missing segment"))?;
+            let count = chars.as_str().parse::<i32>().map_err(|err| {
+                let message = format!("invalid length in segment {:?}: {}", item, err);
+                ParseError::new(1, column + 1, message)
+            })?;
+
+            let p2 = match dir {
+                'R' => Point {
                     x: p1.x + count,
                     ..p1
-                }),
-                'L' => Ok(Point {
+                },
+                'L' => Point {
                     x: p1.x - count,
                     ..p1
-                }),
-                'U' => Ok(Point {
+                },
+                'U' => Point {
                     x: p1.x,
                     y: p1.y + count,
-                }),
-                'D' => Ok(Point {
+                },
+                'D' => Point {
                     x: p1.x,
                     y: p1.y - count,
-                }),
-                _ => Err(ParseWireEror),
-            }?;
+                },
+                _ => {
+                    let message = format!("unknown direction {:?} in segment {:?}", dir, item);
+                    return Err(ParseError::new(1, column, message));
+                }
+            };
 
             segments.push(Line { p1, p2 });
             p1 = p2;
@@ -104,108 +118,136 @@ impl FromStr for Wire {
     }
 }
 
-const POINT_CENTER: Point = Point { x: 0, y: 0 };
-
-pub fn solve(path: &str) -> Result<()> {
-    let input = fs::read_to_string(path)?;
-
-    let wires: std::result::Result<Vec<_>, _> = input.lines().map(|s| Wire::from_str(s)).collect();
-    let wires = wires?;
+const POINT_CENTER: Point = Point::ORIGIN;
 
-    let min_distance = distance_to_near_wires_intersect(&wires[0], &wires[1], POINT_CENTER)
-        .ok_or(anyhow!("No wire intersection"))?;
-    println!("answer 1: {}", min_distance);
+pub struct Day03;
 
-    let steps = min_steps_to_wires_intersect(&wires[0], &wires[1])
-        .ok_or(anyhow!("Couldn't calculate steps to intersection points"))?;
-    println!("answer 2: {}", steps);
-    Ok(())
-}
+impl Solution for Day03 {
+    fn part1(&self, input: &str) -> Result<String> {
+        let wires = parse_wires(input)?;
 
-fn manhattan_distance(pt1: Point, pt2: Point) -> i32 {
-    (pt1.x - pt2.x).abs() + (pt1.y - pt2.y).abs()
-}
+        let min_distance = wires_crossings(&wires)
+            .iter()
+            .map(|c| manhattan_distance(POINT_CENTER, c.point))
+            .min()
+            .ok_or(anyhow!("No wire intersection"))?;
+        Ok(min_distance.to_string())
+    }
 
-fn distance_to_near_wires_intersect(wire1: &Wire, wire2: &Wire, target_pt: Point) -> Option<i32> {
-    let mut min_distance = 0;
+    fn part2(&self, input: &str) -> Result<String> {
+        let wires = parse_wires(input)?;
 
-    for pt in wires_intersection_points(wire1, wire2) {
-        let dist = manhattan_distance(target_pt, pt);
-        if min_distance == 0 || min_distance > dist {
-            min_distance = dist;
-        }
+        let steps = wires_crossings(&wires)
+            .iter()
+            .map(|c| c.steps.0 + c.steps.1)
+            .min()
+            .ok_or(anyhow!("Couldn't calculate steps to intersection points"))?;
+        Ok(steps.to_string())
     }
 
-    if min_distance == 0 {
-        None
-    } else {
-        Some(min_distance)
+    fn validate(&self, input: &str) -> Result<String> {
+        let wires = parse_wires(input)?;
+        let segments: Vec<String> = wires.iter().map(|w| w.segments.len().to_string()).collect();
+        Ok(format!("2 wires with {} segments", segments.join(" and ")))
     }
-}
-
-fn min_steps_to_wires_intersect(wire1: &Wire, wire2: &Wire) -> Option<u32> {
-    let mut min_steps = 0;
 
-    for pt in wires_intersection_points(wire1, wire2) {
-        let wire1_steps = wire1.steps(pt).unwrap();
-        let wire2_steps = wire2.steps(pt).unwrap();
-        let total_steps = wire1_steps + wire2_steps;
-        if min_steps == 0 || min_steps > total_steps {
-            min_steps = total_steps;
+    fn explain(&self, input: &str, explainer: &mut dyn Explainer) -> Result<()> {
+        let wires = parse_wires(input)?;
+
+        explainer.section("Intersections");
+        for Crossing { point: pt, steps } in wires_crossings(&wires) {
+            explainer.note(
+                &format!("({}, {})", pt.x, pt.y),
+                &format_args!(
+                    "distance {}, steps {} + {} = {}",
+                    manhattan_distance(POINT_CENTER, pt),
+                    steps.0,
+                    steps.1,
+                    steps.0 + steps.1
+                ),
+            );
         }
+
+        explainer.section("Part 1");
+        explainer.note("closest distance", &self.part1(input)?);
+        explainer.section("Part 2");
+        explainer.note("fewest combined steps", &self.part2(input)?);
+        Ok(())
     }
 
-    if min_steps == 0 {
-        None
-    } else {
-        Some(min_steps)
+    fn renderable(&self) -> Option<&dyn Renderable> {
+        Some(self)
     }
 }
 
-fn wires_intersection_points(wire1: &Wire, wire2: &Wire) -> Vec<Point> {
-    let mut points = Vec::new();
-
-    // Collect all intersections
-    for line1 in &wire1.segments {
-        for line2 in &wire2.segments {
-            if let Some(point) = straight_lines_intersection(*line1, *line2) {
-                points.push(point);
+// Draws wires one by one, last frame highlights their intersections
+impl Renderable for Day03 {
+    fn render(&self, input: &str, max_width: usize, max_height: usize) -> Result<Vec<Frame>> {
+        let wires = parse_wires(input)?;
+        let bbox = wires[0].bounding_box().union(&wires[1].bounding_box());
+        let (min, max) = (bbox.min, bbox.max);
+
+        // Maps wire coordinates to cells, y axis points up
+        let span = |from: i32, to: i32| (to - from) as i64 + 1;
+        let width = span(min.x, max.x).min(max_width.max(1) as i64);
+        let height = span(min.y, max.y).min(max_height.max(1) as i64);
+        let scale = |v: i32, from: i32, to: i32, cells: i64| {
+            ((v - from) as i64 * (cells - 1) / (span(from, to) - 1).max(1)) as usize
+        };
+        let cell = |pt: Point| {
+            (
+                scale(pt.x, min.x, max.x, width),
+                height as usize - 1 - scale(pt.y, min.y, max.y, height),
+            )
+        };
+
+        let mut frame = Frame::new(width as usize, height as usize);
+        let mut frames = Vec::new();
+        for (i, wire) in wires.iter().enumerate() {
+            for line in wire.segments() {
+                for pt in line.points() {
+                    let (x, y) = cell(pt);
+                    frame.set(x, y, Style::Trace(i as u8));
+                }
             }
+            let (x, y) = cell(POINT_CENTER);
+            frame.set(x, y, Style::Origin);
+            frames.push(frame.clone());
         }
-    }
 
-    points
+        for crossing in wires_crossings(&wires) {
+            let (x, y) = cell(crossing.point);
+            frame.set(x, y, Style::Highlight);
+        }
+        frames.push(frame);
+
+        Ok(frames)
+    }
 }
 
-fn is_value_in_range(start: i32, end: i32, val: i32) -> bool {
-    if start > end {
-        return is_value_in_range(end, start, val);
+fn parse_wires(input: &str) -> Result<Vec<Wire>> {
+    let wires = input
+        .lines()
+        .enumerate()
+        .map(|(i, l)| {
+            Wire::from_str(l)
+                .map_err(|err| err.with_line(i + 1))
+                .context("Invalid wire")
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    if wires.len() != 2 {
+        bail!("Expected 2 wires, found {}", wires.len());
     }
-    return start <= val && val <= end;
+    Ok(wires)
 }
 
-fn straight_lines_intersection(l1: Line, l2: Line) -> Option<Point> {
-    return match (l1.p1, l1.p2, l2.p1, l2.p2) {
-        (p1, p2, p3, p4) if p1.x == p2.x && p3.x != p4.x => {
-            // l1 is vertical and l2 is horizontal
-            if is_value_in_range(p1.y, p2.y, p3.y) && is_value_in_range(p3.x, p4.x, p1.x) {
-                // println!("V: {:?} {:?} | {:?} {:?}", p1, p2, p3, p4);
-                Some(Point { x: p1.x, ..p3 })
-            } else {
-                None
-            }
-        }
-        (p1, p2, p3, p4) if p1.y == p2.y && p3.y != p4.y => {
-            // l1 is horizontal and l2 is vectical
-            if is_value_in_range(p1.x, p2.x, p3.x) && is_value_in_range(p3.y, p4.y, p1.y) {
-                // println!("H: {:?} {:?} | {:?} {:?}", p1, p2, p3, p4);
-                Some(Point { x: p3.x, ..p1 })
-            } else {
-                None
-            }
-        }
-        _ => None,
-    };
+// Points where wires cross, the central port doesn't count
+fn wires_crossings(wires: &[Wire]) -> Vec<Crossing> {
+    geometry::crossings(&wires[0].segments, &wires[1].segments)
+        .into_iter()
+        .filter(|c| c.point != POINT_CENTER)
+        .collect()
 }
 
 #[cfg(test)]
@@ -222,7 +264,7 @@ mod tests {
     #[test]
     fn test_parse_wire() {
         let wire = Wire::from_str("R8, U5, L5, D3");
-        assert_eq!(wire.is_ok(), true);
+        assert!(wire.is_ok());
         let wire = wire.unwrap();
         assert_eq!(
             wire.segments,
@@ -235,7 +277,7 @@ mod tests {
         );
 
         let wire = Wire::from_str("U7,R6,D4,L4");
-        assert_eq!(wire.is_ok(), true);
+        assert!(wire.is_ok());
         let wire = wire.unwrap();
         assert_eq!(
             wire.segments,
@@ -249,29 +291,27 @@ mod tests {
     }
 
     #[test]
-    fn test_lowest_wire_distance() {
-        let cases = [
-            ("R8,U5,L5,D3", "U7,R6,D4,L4", Some(6)),
-            (
-                "R75,D30,R83,U83,L12,D49,R71,U7,L72",
-                "U62,R66,U55,R34,D71,R55,D58,R83",
-                Some(159),
-            ),
-            (
-                "R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51",
-                "U98,R91,D20,R16,D67,R40,U7,R15,U6,R7",
-                Some(135),
-            ),
-        ];
-
-        for (in1, in2, res) in cases {
-            let wire1 = in1.parse::<Wire>().unwrap();
-            let wire2 = in2.parse::<Wire>().unwrap();
-            assert_eq!(
-                distance_to_near_wires_intersect(&wire1, &wire2, POINT_CENTER),
-                res
-            );
-        }
+    fn test_validate() {
+        assert_eq!(
+            Day03.validate("R8,U5,L5,D3\nU7,R6,D4").unwrap(),
+            "2 wires with 4 and 3 segments"
+        );
+        assert!(Day03.validate("R8,U5,L5,D3").is_err());
+        let err = Day03.validate("R8,U5\nU7,X6").unwrap_err();
+        assert_eq!(
+            format!("{:#}", err),
+            "Invalid wire: line 2, column 4: unknown direction 'X' in segment \"X6\""
+        );
+        let err = Day03.validate("R8,U5\nU7,,R1").unwrap_err();
+        assert_eq!(
+            format!("{:#}", err),
+            "Invalid wire: line 2, column 4: missing segment"
+        );
+        let err = Day03.validate("R8,Ux\nU7").unwrap_err();
+        assert_eq!(
+            format!("{:#}", err),
+            "Invalid wire: line 1, column 5: invalid length in segment \"Ux\": invalid digit found in string"
+        );
     }
 
     #[test]
@@ -286,57 +326,100 @@ mod tests {
     }
 
     #[test]
-    fn test_min_wire_steps() {
-        let cases = [
-            ("R8,U5,L5,D3", "U7,R6,D4,L4", Some(30)),
-            (
-                "R75,D30,R83,U83,L12,D49,R71,U7,L72",
-                "U62,R66,U55,R34,D71,R55,D58,R83",
-                Some(610),
-            ),
-            (
-                "R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51",
-                "U98,R91,D20,R16,D67,R40,U7,R15,U6,R7",
-                Some(410),
-            ),
-        ];
-
-        for (in1, in2, res) in cases {
-            let wire1 = in1.parse::<Wire>().unwrap();
-            let wire2 = in2.parse::<Wire>().unwrap();
-            assert_eq!(min_steps_to_wires_intersect(&wire1, &wire2), res);
-        }
+    fn test_wire_stats() {
+        let wire = Wire::from_str("R8,U5,L5,D3").unwrap();
+        assert_eq!(wire.length(), 21);
+        assert_eq!(wire.segments().count(), 4);
+        assert_eq!(wire.segments().last(), Some(build_line((3, 5), (3, 2))));
+
+        let bbox = wire.bounding_box();
+        assert_eq!(bbox.min, Point { x: 0, y: 0 });
+        assert_eq!(bbox.max, Point { x: 8, y: 5 });
+        assert_eq!((bbox.width(), bbox.height()), (9, 6));
+
+        let wire = Wire::from_str("L2,D3").unwrap();
+        let bbox = wire.bounding_box();
+        assert_eq!(bbox.min, Point { x: -2, y: -3 });
+        assert_eq!(bbox.max, Point { x: 0, y: 0 });
     }
 
     #[test]
-    fn test_lines_intersection() {
-        let l1 = build_line((3, 5), (3, 3));
-        let l2 = build_line((6, 3), (2, 3));
-        assert_eq!(
-            straight_lines_intersection(l1, l2),
-            Some(Point { x: 3, y: 3 })
-        );
-        assert_eq!(
-            straight_lines_intersection(l2, l1),
-            Some(Point { x: 3, y: 3 })
-        );
+    fn test_wire_point_at() {
+        let wire = Wire::from_str("R8,U5,L5,D3").unwrap();
+        assert_eq!(wire.point_at(0), Some(Point { x: 0, y: 0 }));
+        assert_eq!(wire.point_at(8), Some(Point { x: 8, y: 0 }));
+        assert_eq!(wire.point_at(10), Some(Point { x: 8, y: 2 }));
+        assert_eq!(wire.point_at(15), Some(Point { x: 6, y: 5 }));
+        assert_eq!(wire.point_at(21), Some(Point { x: 3, y: 2 }));
+        assert_eq!(wire.point_at(22), None);
+
+        // Wire doesn't cross itself, so walking to a point takes exactly the steps
+        // reported for it
+        for steps in 0..=wire.length() {
+            let pt = wire.point_at(steps).unwrap();
+            assert_eq!(wire.steps(pt).unwrap() as u64, steps);
+        }
 
-        let l1 = build_line((8, 0), (8, 5));
-        let l2 = build_line((0, 7), (6, 7));
-        assert_eq!(straight_lines_intersection(l1, l2), None);
+        // Points visited again report steps of the first visit
+        let wire = Wire::from_str("R2,U1,L1,D2").unwrap();
+        assert_eq!(wire.point_at(5), Some(Point { x: 1, y: 0 }));
+        assert_eq!(wire.steps(Point { x: 1, y: 0 }), Some(1));
+    }
 
-        let l1 = build_line((8, 5), (3, 5));
-        let l2 = build_line((6, 7), (6, 3));
+    #[test]
+    fn test_render() {
+        let frames = Day03.render("R8,U5,L5,D3\nU7,R6,D4,L4", 80, 40).unwrap();
+        assert_eq!(frames.len(), 3);
         assert_eq!(
-            straight_lines_intersection(l1, l2),
-            Some(Point { x: 6, y: 5 })
+            frames[2].to_string(),
+            "\
+2222222..
+2.....2..
+2..111X11
+2..1..2.1
+2.2X222.1
+2..1....1
+2.......1
+o11111111
+"
         );
 
-        let l1 = build_line((0, 0), (8, 0));
-        let l2 = build_line((0, 0), (0, 7));
-        assert_eq!(
-            straight_lines_intersection(l1, l2),
-            Some(Point { x: 0, y: 0 })
-        );
+        // Large wires are scaled down to fit
+        let frames = Day03.render("R1000,U500\nU1000", 10, 5).unwrap();
+        assert_eq!((frames[0].width(), frames[0].height()), (10, 5));
+        assert_eq!(frames[0].get(0, 4), Some(Style::Origin));
+        assert_eq!(frames[0].get(9, 2), Some(Style::Trace(0)));
     }
+
+    #[test]
+    fn test_collinear_crossings() {
+        // Wires running along each other cross at every shared point
+        let input = "R5,U2\nL1,R4,U3";
+        assert_eq!(Day03.part1(input).unwrap(), "1");
+        assert_eq!(Day03.part2(input).unwrap(), "4");
+    }
+
+    aoc_example!(
+        example1,
+        Day03,
+        "R8,U5,L5,D3\nU7,R6,D4,L4",
+        part1 = "6",
+        part2 = "30",
+    );
+
+    aoc_example!(
+        example2,
+        Day03,
+        "R75,D30,R83,U83,L12,D49,R71,U7,L72\nU62,R66,U55,R34,D71,R55,D58,R83",
+        part1 = "159",
+        part2 = "610",
+    );
+
+    aoc_example!(
+        example3,
+        Day03,
+        "R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51\nU98,R91,D20,R16,D67,R40,U7,R15,U6,R7",
+        part1 = "135",
+        part2 = "410",
+    );
 }
@@ -1,27 +1,156 @@
+use crate::explain::Explainer;
+use crate::parse::{self, ParseError};
+use crate::progress::{NoProgress, Progress};
+use crate::search;
+use crate::Solution;
 use anyhow::Result;
-use std::fs;
+use std::ops::RangeInclusive;
 
-pub fn solve(path: &str) -> Result<()> {
-    let input = fs::read_to_string(path)?;
+// Number of digits in every password
+const DIGITS: usize = 6;
 
-    let range = input
-        .split('-')
-        .map(|s| s.trim().parse::<u32>())
-        .collect::<Result<Vec<_>, _>>()?;
-    assert!(range.len() == 2);
+pub struct Day04;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rules {
+    // Digits never decrease and two adjacent digits are the same
+    Adjacent,
+    // Like Adjacent, but the pair must not be part of a larger group of matching digits
+    ExactDouble,
+}
+
+impl Rules {
+    pub fn matches(&self, pass: u32) -> bool {
+        let (adjacent, exact_double) = is_password_good(pass);
+        match self {
+            Self::Adjacent => adjacent,
+            Self::ExactDouble => exact_double,
+        }
+    }
+}
+
+// Iterates over passwords in range which satisfy the rules
+#[derive(Debug, Clone)]
+pub struct PasswordCandidates {
+    range: RangeInclusive<u32>,
+    rules: Rules,
+}
 
-    let mut answer1 = 0usize;
-    let mut answer2 = 0usize;
-    for i in range[0]..=range[1] {
-        let (part1, part2) = is_password_good(i);
-        // Just for fun. Should be done with if part1/part2.
-        answer1 += part1 as usize;
-        answer2 += part2 as usize;
+impl PasswordCandidates {
+    pub fn new(range: RangeInclusive<u32>, rules: Rules) -> PasswordCandidates {
+        PasswordCandidates { range, rules }
     }
+}
+
+impl Iterator for PasswordCandidates {
+    type Item = u32;
 
-    println!("answer 1: {}", answer1);
-    println!("answer 2: {}", answer2);
-    Ok(())
+    fn next(&mut self) -> Option<u32> {
+        let rules = self.rules;
+        self.range.find(|&pass| rules.matches(pass))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.range.size_hint().1)
+    }
+}
+
+impl Solution for Day04 {
+    fn part1(&self, input: &str) -> Result<String> {
+        self.part1_with_progress(input, &NoProgress)
+    }
+
+    fn part2(&self, input: &str) -> Result<String> {
+        self.part2_with_progress(input, &NoProgress)
+    }
+
+    fn part1_with_progress(&self, input: &str, progress: &dyn Progress) -> Result<String> {
+        let (start, end) = parse_range(input)?;
+        Ok(count_passwords(start..=end, Rules::Adjacent, progress)?.to_string())
+    }
+
+    fn part2_with_progress(&self, input: &str, progress: &dyn Progress) -> Result<String> {
+        let (start, end) = parse_range(input)?;
+        Ok(count_passwords(start..=end, Rules::ExactDouble, progress)?.to_string())
+    }
+
+    fn validate(&self, input: &str) -> Result<String> {
+        let (start, end) = parse_range(input)?;
+        Ok(format!("{} passwords in range", (end - start) as u64 + 1))
+    }
+
+    // Counts passwords passing every rule separately
+    fn explain(&self, input: &str, explainer: &mut dyn Explainer) -> Result<()> {
+        let (start, end) = parse_range(input)?;
+        let increasing = (start..=end)
+            .filter(|&pass| to_digits(pass).windows(2).all(|w| w[0] <= w[1]))
+            .count();
+
+        explainer.section("Rules");
+        explainer.note("passwords in range", &((end - start) as u64 + 1));
+        explainer.note("digits never decrease", &increasing);
+        explainer.section("Part 1");
+        explainer.note(
+            "and two adjacent digits match",
+            &PasswordCandidates::new(start..=end, Rules::Adjacent).count(),
+        );
+        explainer.section("Part 2");
+        explainer.note(
+            "and exactly two adjacent digits match",
+            &PasswordCandidates::new(start..=end, Rules::ExactDouble).count(),
+        );
+        Ok(())
+    }
+}
+
+// Counts candidates in parallel, reporting progress as they are checked
+fn count_passwords(
+    range: RangeInclusive<u32>,
+    rules: Rules,
+    progress: &dyn Progress,
+) -> Result<usize> {
+    search::parallel_count_range(range, progress, |pass| rules.matches(pass))
+}
+
+// Parses range like 123456-654321 written on a single line
+fn parse_range(input: &str) -> Result<(u32, u32), ParseError> {
+    let input = input.trim();
+    let mut lines = input.lines();
+    let line = lines.next().unwrap_or_default();
+    if lines.next().is_some() {
+        return Err(ParseError::new(2, 1, "expected single line with range"));
+    }
+
+    let bounds = parse::tokens(line, |c| c == '-')
+        .map(|(column, token)| {
+            // Sign is accepted by the number parser, but not part of the bound
+            if !token.bytes().all(|b| b.is_ascii_digit()) {
+                let message = format!("invalid range bound {:?}: expected only digits", token);
+                return Err(ParseError::new(1, column, message));
+            }
+            let bound = token.parse::<u32>().map_err(|err| {
+                let message = format!("invalid range bound {:?}: {}", token, err);
+                ParseError::new(1, column, message)
+            })?;
+            if token.len() != DIGITS {
+                let message = format!("range bound {} is not a {}-digit number", token, DIGITS);
+                return Err(ParseError::new(1, column, message));
+            }
+            Ok(bound)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match bounds[..] {
+        [start, end] if start <= end => Ok((start, end)),
+        [start, end] => {
+            let message = format!("range start {} is greater than end {}", start, end);
+            Err(ParseError::new(1, 1, message))
+        }
+        _ => {
+            let message = format!("expected range like 123456-654321, found {:?}", line);
+            Err(ParseError::new(1, 1, message))
+        }
+    }
 }
 
 fn is_password_good(pass: u32) -> (bool, bool) {
@@ -31,9 +160,9 @@ fn is_password_good(pass: u32) -> (bool, bool) {
     let mut has_double = false;
     let mut has_adjacent = false;
     let (last_group_size, _) = digits.iter().fold((1, None), |(mut group, prev), digit| {
-        if prev.is_some() {
-            increases = increases && *digit >= prev.unwrap();
-            if *digit == prev.unwrap() {
+        if let Some(prev) = prev {
+            increases = increases && *digit >= prev;
+            if *digit == prev {
                 has_adjacent = true;
                 group += 1;
             } else {
@@ -56,7 +185,7 @@ fn to_digits(mut num: u32) -> Vec<u8> {
 
     while num > 0 {
         digits.push((num % 10) as u8);
-        num = num / 10;
+        num /= 10;
     }
 
     digits.reverse();
@@ -66,26 +195,177 @@ fn to_digits(mut num: u32) -> Vec<u8> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cancel::{CancellationToken, Cancelled};
+    use crate::explain::Explanation;
+    use crate::progress::ProgressBar;
+    use std::sync::Mutex;
 
     #[test]
-    fn test_to_digits() {
-        assert_eq!(to_digits(223450u32), [2, 2, 3, 4, 5, 0]);
+    fn test_password_candidates() {
+        let candidates: Vec<u32> =
+            PasswordCandidates::new(111110..=111125, Rules::Adjacent).collect();
+        assert_eq!(
+            candidates,
+            [
+                111111, 111112, 111113, 111114, 111115, 111116, 111117, 111118, 111119, 111122,
+                111123, 111124, 111125
+            ]
+        );
+
+        let candidates: Vec<u32> =
+            PasswordCandidates::new(111110..=111125, Rules::ExactDouble).collect();
+        assert_eq!(candidates, [111122]);
+
+        assert_eq!(
+            PasswordCandidates::new(135679..=135679, Rules::Adjacent).count(),
+            0
+        );
     }
 
     #[test]
-    fn test() {
-        let cases = [
-            (111111u32, (true, false)),
-            (123444, (true, false)),
-            (111123, (true, false)),
-            (135679, (false, false)),
-            (223450, (false, false)),
-            (123789, (false, false)),
-            (676399, (false, false)),
-        ];
-
-        for (pass, res) in cases {
-            assert_eq!(is_password_good(pass), res, "pass {}", pass);
+    fn test_count_passwords_progress() {
+        struct Updates(Mutex<Vec<(u64, u64)>>);
+        impl Progress for Updates {
+            fn update(&self, done: u64, total: u64) {
+                self.0.lock().unwrap().push((done, total));
+            }
         }
+
+        let updates = Updates(Mutex::new(Vec::new()));
+        assert_eq!(
+            count_passwords(111110..=111125, Rules::Adjacent, &updates).unwrap(),
+            13
+        );
+        let updates = updates.0.into_inner().unwrap();
+        assert_eq!(updates.len(), 16);
+        assert_eq!(updates.last(), Some(&(16, 16)));
+        assert!(updates.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[test]
+    fn test_count_passwords_cancelled() {
+        let token = CancellationToken::new();
+        token.cancel();
+        let progress = ProgressBar::new().cancellable(token);
+        let err = count_passwords(111110..=111125, Rules::Adjacent, &progress).unwrap_err();
+        assert!(err.is::<Cancelled>());
+    }
+
+    #[test]
+    fn test_explain() {
+        let mut explanation = Explanation::default();
+        Day04.explain("111110-111125", &mut explanation).unwrap();
+        assert_eq!(
+            explanation.to_string(),
+            "\
+Rules
+  passwords in range: 16
+  digits never decrease: 13
+Part 1
+  and two adjacent digits match: 13
+Part 2
+  and exactly two adjacent digits match: 1
+"
+        );
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("134564-585159\n").unwrap(), (134564, 585159));
+
+        let err = |input| parse_range(input).unwrap_err().to_string();
+        assert_eq!(
+            err("134564-58x159"),
+            "line 1, column 8: invalid range bound \"58x159\": expected only digits"
+        );
+        assert_eq!(
+            err("+12345-654321"),
+            "line 1, column 1: invalid range bound \"+12345\": expected only digits"
+        );
+        assert_eq!(
+            err("-654321"),
+            "line 1, column 1: invalid range bound \"\": cannot parse integer from empty string"
+        );
+        assert_eq!(
+            err("585159-134564"),
+            "line 1, column 1: range start 585159 is greater than end 134564"
+        );
+        assert_eq!(
+            err("13456-585159"),
+            "line 1, column 1: range bound 13456 is not a 6-digit number"
+        );
+        assert_eq!(
+            err("134564-5851590"),
+            "line 1, column 8: range bound 5851590 is not a 6-digit number"
+        );
+        assert_eq!(
+            err("134564"),
+            "line 1, column 1: expected range like 123456-654321, found \"134564\""
+        );
+        assert_eq!(
+            err("1-2\n3-4"),
+            "line 2, column 1: expected single line with range"
+        );
     }
+
+    #[test]
+    fn test_to_digits() {
+        assert_eq!(to_digits(223450u32), [2, 2, 3, 4, 5, 0]);
+    }
+
+    aoc_example!(
+        password_111111,
+        Day04,
+        "111111-111111",
+        part1 = "1",
+        part2 = "0",
+    );
+
+    aoc_example!(
+        password_123444,
+        Day04,
+        "123444-123444",
+        part1 = "1",
+        part2 = "0",
+    );
+
+    aoc_example!(
+        password_111123,
+        Day04,
+        "111123-111123",
+        part1 = "1",
+        part2 = "0",
+    );
+
+    aoc_example!(
+        password_135679,
+        Day04,
+        "135679-135679",
+        part1 = "0",
+        part2 = "0",
+    );
+
+    aoc_example!(
+        password_223450,
+        Day04,
+        "223450-223450",
+        part1 = "0",
+        part2 = "0",
+    );
+
+    aoc_example!(
+        password_123789,
+        Day04,
+        "123789-123789",
+        part1 = "0",
+        part2 = "0",
+    );
+
+    aoc_example!(
+        password_676399,
+        Day04,
+        "676399-676399",
+        part1 = "0",
+        part2 = "0",
+    );
 }
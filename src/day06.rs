@@ -0,0 +1,174 @@
+use crate::explain::Explainer;
+use crate::graph::{Graph, Tree};
+use crate::parse::ParseError;
+use crate::Solution;
+use anyhow::{anyhow, bail, Context, Result};
+
+const YOU: &str = "YOU";
+const SANTA: &str = "SAN";
+
+pub struct Day06;
+
+impl Solution for Day06 {
+    fn part1(&self, input: &str) -> Result<String> {
+        let orbits = parse_orbits(input)?;
+        Ok(total_orbits(&orbits)?.to_string())
+    }
+
+    fn part2(&self, input: &str) -> Result<String> {
+        let orbits = parse_orbits(input)?;
+        let path = transfer_path(&orbits)?;
+        Ok((path.len() - 1).to_string())
+    }
+
+    fn validate(&self, input: &str) -> Result<String> {
+        let orbits = parse_orbits(input)?;
+        let objects = orbits.children().len();
+        total_orbits(&orbits)?;
+        Ok(format!(
+            "{} objects, {} orbits",
+            objects,
+            input.lines().count()
+        ))
+    }
+
+    fn explain(&self, input: &str, explainer: &mut dyn Explainer) -> Result<()> {
+        let orbits = parse_orbits(input)?;
+
+        explainer.section("Part 1");
+        explainer.note("objects", &orbits.children().len());
+        explainer.note("direct and indirect orbits", &self.part1(input)?);
+
+        explainer.section("Part 2");
+        for object in [YOU, SANTA] {
+            explainer.note(
+                &format!("{} orbits", object),
+                &orbits.parent(object).unwrap_or("nothing"),
+            );
+        }
+        if let Some(common) = orbits.common_ancestor(YOU, SANTA) {
+            explainer.note("closest common center", &common);
+        }
+        explainer.note("orbital transfers", &self.part2(input)?);
+        Ok(())
+    }
+}
+
+// Every object orbits exactly one other, lines look like COM)B
+fn parse_orbits(input: &str) -> Result<Tree<&str>> {
+    let mut orbits = Tree::new();
+    for (i, line) in input.lines().enumerate() {
+        let line = line.trim();
+        let (center, object) = line
+            .split_once(')')
+            .ok_or_else(|| ParseError::new(i + 1, 1, format!("expected A)B, found {:?}", line)))
+            .context("Invalid orbit")?;
+        if center.is_empty() || object.is_empty() {
+            let err = ParseError::new(i + 1, 1, format!("missing object in {:?}", line));
+            return Err(err).context("Invalid orbit");
+        }
+
+        if let Some(previous) = orbits.set_parent(object, center) {
+            let message = format!("{} already orbits {}", object, previous);
+            let column = center.chars().count() + 2;
+            return Err(ParseError::new(i + 1, column, message)).context("Invalid orbit");
+        }
+    }
+
+    Ok(orbits)
+}
+
+// Sum of depths of every object in the orbit tree
+fn total_orbits(orbits: &Tree<&str>) -> Result<usize> {
+    let depths = orbits.depths().ok_or(anyhow!("Orbits form a cycle"))?;
+    Ok(depths.values().sum())
+}
+
+// Objects YOU and SAN orbit and every object between them
+fn transfer_path<'a>(orbits: &Tree<&'a str>) -> Result<Vec<&'a str>> {
+    let center = |object| {
+        orbits
+            .parent(object)
+            .ok_or_else(|| anyhow!("{} doesn't orbit anything", object))
+    };
+    let (from, to) = (center(YOU)?, center(SANTA)?);
+
+    let mut graph = Graph::new();
+    for node in orbits.children().nodes() {
+        if let Some(parent) = orbits.parent(node) {
+            graph.add_undirected_edge(node, parent);
+        }
+    }
+    match graph.shortest_path(from, to) {
+        Some(path) => Ok(path),
+        None => bail!("{} and {} don't orbit the same center", YOU, SANTA),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAP: &str = "COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L";
+
+    #[test]
+    fn test_parse_orbits() {
+        let orbits = parse_orbits(MAP).unwrap();
+        assert_eq!(orbits.parent("L"), Some("K"));
+        assert_eq!(orbits.parent("COM"), None);
+        assert_eq!(total_orbits(&orbits).unwrap(), 42);
+
+        let err = Day06.validate("COM)B\nB-C").unwrap_err();
+        assert_eq!(
+            format!("{:#}", err),
+            "Invalid orbit: line 2, column 1: expected A)B, found \"B-C\""
+        );
+        let err = Day06.validate("COM)B\nB)\n").unwrap_err();
+        assert_eq!(
+            format!("{:#}", err),
+            "Invalid orbit: line 2, column 1: missing object in \"B)\""
+        );
+        let err = Day06.validate("COM)B\nCOM)C\nX)C").unwrap_err();
+        assert_eq!(
+            format!("{:#}", err),
+            "Invalid orbit: line 3, column 3: C already orbits COM"
+        );
+        let err = Day06.validate("COM)B\nC)D\nD)C").unwrap_err();
+        assert_eq!(format!("{:#}", err), "Orbits form a cycle");
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(Day06.validate(MAP).unwrap(), "12 objects, 11 orbits");
+    }
+
+    #[test]
+    fn test_transfer_path() {
+        let input = format!("{}\nK)YOU\nI)SAN", MAP);
+        let orbits = parse_orbits(&input).unwrap();
+        assert_eq!(transfer_path(&orbits).unwrap(), ["K", "J", "E", "D", "I"]);
+        assert_eq!(orbits.common_ancestor(YOU, SANTA), Some("D"));
+
+        let err = Day06.part2(MAP).unwrap_err();
+        assert_eq!(err.to_string(), "YOU doesn't orbit anything");
+        let err = Day06
+            .part2(&format!("{}\nK)YOU\nX)Y\nY)SAN", MAP))
+            .unwrap_err();
+        assert_eq!(err.to_string(), "YOU and SAN don't orbit the same center");
+    }
+
+    aoc_example!(
+        example1,
+        Day06,
+        "COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L",
+        part1 = "42",
+    );
+
+    aoc_example!(
+        example2,
+        Day06,
+        "COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L\nK)YOU\nI)SAN",
+        part1 = "54",
+        part2 = "4",
+    );
+}
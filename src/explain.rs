@@ -0,0 +1,79 @@
+// Intermediate results solvers report to show how the answer was found
+use std::fmt;
+
+// Receives results in the order solver finds them
+pub trait Explainer {
+    // Starts group of related results, e.g. one per part
+    fn section(&mut self, title: &str);
+    fn note(&mut self, key: &str, value: &dyn fmt::Display);
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    pub title: String,
+    pub notes: Vec<(String, String)>,
+}
+
+// Collects everything solver reported, printed as indented list
+#[derive(Debug, Default)]
+pub struct Explanation {
+    pub sections: Vec<Section>,
+}
+
+impl Explanation {
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+}
+
+impl Explainer for Explanation {
+    fn section(&mut self, title: &str) {
+        self.sections.push(Section {
+            title: title.to_string(),
+            notes: Vec::new(),
+        });
+    }
+
+    // Notes reported before the first section get an untitled one
+    fn note(&mut self, key: &str, value: &dyn fmt::Display) {
+        if self.sections.is_empty() {
+            self.section("");
+        }
+        let section = self.sections.last_mut().unwrap();
+        section.notes.push((key.to_string(), value.to_string()));
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for section in &self.sections {
+            if !section.title.is_empty() {
+                writeln!(f, "{}", section.title)?;
+            }
+            for (key, value) in &section.notes {
+                writeln!(f, "  {}: {}", key, value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explanation() {
+        let mut explanation = Explanation::default();
+        assert!(explanation.is_empty());
+
+        explanation.note("modules", &2);
+        explanation.section("Part 1");
+        explanation.note("module 1", &"fuel 2");
+        assert_eq!(explanation.sections.len(), 2);
+        assert_eq!(
+            explanation.to_string(),
+            "  modules: 2\nPart 1\n  module 1: fuel 2\n"
+        );
+    }
+}
@@ -0,0 +1,481 @@
+// Writes frames of Renderable solutions to animated GIF or APNG files.
+// Every cell becomes a square of scale x scale pixels colored by its style.
+use crate::render::{Frame, Style};
+use anyhow::{bail, Context, Result};
+use flate2::write::ZlibEncoder;
+use flate2::{Compression, Crc};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::iter;
+use std::path::Path;
+use std::time::Duration;
+
+// Colors of styles, traces cycle through nine colors like their glyphs do
+const PALETTE: [[u8; 3]; 16] = [
+    [0x10, 0x10, 0x18],
+    [0xff, 0xff, 0xff],
+    [0xff, 0x30, 0x30],
+    [0x4c, 0x9a, 0xff],
+    [0x50, 0xd0, 0x60],
+    [0xff, 0xc8, 0x3c],
+    [0xc0, 0x70, 0xff],
+    [0x40, 0xe0, 0xe0],
+    [0xff, 0x8c, 0x40],
+    [0xff, 0x70, 0xb0],
+    [0xa0, 0xa0, 0xa0],
+    [0x90, 0xa0, 0x40],
+    [0, 0, 0],
+    [0, 0, 0],
+    [0, 0, 0],
+    [0, 0, 0],
+];
+
+fn color_index(style: Style) -> u8 {
+    match style {
+        Style::Empty => 0,
+        Style::Origin => 1,
+        Style::Highlight => 2,
+        Style::Trace(n) => 3 + n % 9,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Gif,
+    Apng,
+}
+
+impl Format {
+    // Chosen by file extension, .png and .apng files are APNG
+    pub fn from_path(path: &str) -> Result<Format> {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("gif") => Ok(Format::Gif),
+            Some("png" | "apng") => Ok(Format::Apng),
+            _ => bail!("Unknown animation format of {}, use .gif or .png", path),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    // Pixels per cell side
+    pub scale: usize,
+    // Time every frame is shown
+    pub delay: Duration,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            scale: 4,
+            delay: Duration::from_millis(100),
+        }
+    }
+}
+
+// Encodes frames in format chosen by path and writes them to it
+pub fn write(path: &str, frames: &[Frame], options: &Options) -> Result<()> {
+    let data = encode(frames, Format::from_path(path)?, options)?;
+    fs::write(path, data).with_context(|| format!("Failed to write {}", path))
+}
+
+// Animation looping forever, every frame must have the same size
+pub fn encode(frames: &[Frame], format: Format, options: &Options) -> Result<Vec<u8>> {
+    let Some(first) = frames.first() else {
+        bail!("No frames to export");
+    };
+    if frames
+        .iter()
+        .any(|f| (f.width(), f.height()) != (first.width(), first.height()))
+    {
+        bail!("Frames of one animation must have the same size");
+    }
+
+    let scale = options.scale.max(1);
+    let size = |cells: usize| cells.checked_mul(scale).filter(|&pixels| pixels > 0);
+    let (Some(width), Some(height)) = (size(first.width()), size(first.height())) else {
+        bail!(
+            "Image of {}x{} cells can't be scaled by {}",
+            first.width(),
+            first.height(),
+            scale
+        );
+    };
+
+    let images = frames.iter().map(|frame| pixels(frame, scale));
+    match format {
+        Format::Gif => gif(images, width, height, options.delay),
+        Format::Apng => apng(images, frames.len(), width, height, options.delay),
+    }
+}
+
+// Palette indices of scaled frame, row by row
+fn pixels(frame: &Frame, scale: usize) -> Vec<u8> {
+    let mut pixels = Vec::with_capacity(frame.width() * frame.height() * scale * scale);
+    for row in frame.rows() {
+        let line: Vec<u8> = row
+            .iter()
+            .flat_map(|&style| iter::repeat_n(color_index(style), scale))
+            .collect();
+        for _ in 0..scale {
+            pixels.extend_from_slice(&line);
+        }
+    }
+    pixels
+}
+
+// Bits per pixel of GIF palette with 16 colors
+const GIF_BITS: u8 = 4;
+// LZW codes are at most 12 bits long
+const GIF_MAX_CODES: u16 = 1 << 12;
+
+fn gif(
+    images: impl Iterator<Item = Vec<u8>>,
+    width: usize,
+    height: usize,
+    delay: Duration,
+) -> Result<Vec<u8>> {
+    let (Ok(width), Ok(height)) = (u16::try_from(width), u16::try_from(height)) else {
+        bail!("GIF image of {}x{} pixels is too large", width, height);
+    };
+    // Delay is given in hundredths of a second
+    let delay = u16::try_from(delay.as_millis() / 10).unwrap_or(u16::MAX);
+
+    let mut out = b"GIF89a".to_vec();
+    out.extend(width.to_le_bytes());
+    out.extend(height.to_le_bytes());
+    // Global color table with 2^GIF_BITS colors, background color and aspect ratio
+    let bits = GIF_BITS - 1;
+    out.extend([0x80 | bits << 4 | bits, 0, 0]);
+    out.extend(PALETTE.iter().flatten());
+    // Application extension making the animation loop forever
+    out.extend([0x21, 0xff, 0x0b]);
+    out.extend(b"NETSCAPE2.0");
+    out.extend([0x03, 0x01, 0x00, 0x00, 0x00]);
+
+    for image in images {
+        // Graphic control extension with frame delay
+        out.extend([0x21, 0xf9, 0x04, 0x00]);
+        out.extend(delay.to_le_bytes());
+        out.extend([0x00, 0x00]);
+
+        // Image descriptor covering the whole screen
+        out.extend([0x2c, 0, 0, 0, 0]);
+        out.extend(width.to_le_bytes());
+        out.extend(height.to_le_bytes());
+        out.push(0);
+
+        out.push(GIF_BITS);
+        for block in lzw(&image, GIF_BITS).chunks(255) {
+            out.push(block.len() as u8);
+            out.extend_from_slice(block);
+        }
+        out.push(0);
+    }
+
+    out.push(0x3b);
+    Ok(out)
+}
+
+// Variable length LZW codes GIF images are compressed with, packed starting
+// from the least significant bit
+fn lzw(indices: &[u8], min_bits: u8) -> Vec<u8> {
+    let clear = 1u16 << min_bits;
+    let end = clear + 1;
+    let mut bits = BitWriter::default();
+    let mut codes: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut size = min_bits + 1;
+    let mut next = end + 1;
+
+    bits.push(clear, size);
+    let mut indices = indices.iter();
+    let Some(&first) = indices.next() else {
+        bits.push(end, size);
+        return bits.finish();
+    };
+
+    let mut code = first as u16;
+    for &index in indices {
+        if let Some(&known) = codes.get(&(code, index)) {
+            code = known;
+            continue;
+        }
+
+        bits.push(code, size);
+        if next == GIF_MAX_CODES {
+            // Table is full, decoder starts from scratch as well
+            bits.push(clear, size);
+            codes.clear();
+            size = min_bits + 1;
+            next = end + 1;
+        } else {
+            if next == 1 << size {
+                size += 1;
+            }
+            codes.insert((code, index), next);
+            next += 1;
+        }
+        code = index as u16;
+    }
+
+    bits.push(code, size);
+    bits.push(end, size);
+    bits.finish()
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    pending: u32,
+    len: u8,
+}
+
+impl BitWriter {
+    fn push(&mut self, code: u16, size: u8) {
+        self.pending |= (code as u32) << self.len;
+        self.len += size;
+        while self.len >= 8 {
+            self.bytes.push(self.pending as u8);
+            self.pending >>= 8;
+            self.len -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.len > 0 {
+            self.bytes.push(self.pending as u8);
+        }
+        self.bytes
+    }
+}
+
+fn apng(
+    images: impl Iterator<Item = Vec<u8>>,
+    frames: usize,
+    width: usize,
+    height: usize,
+    delay: Duration,
+) -> Result<Vec<u8>> {
+    let (Ok(width), Ok(height)) = (u32::try_from(width), u32::try_from(height)) else {
+        bail!("PNG image of {}x{} pixels is too large", width, height);
+    };
+    // Delay is given as fraction of a second, milliseconds here
+    let delay = u16::try_from(delay.as_millis()).unwrap_or(u16::MAX);
+
+    let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+    // 8 bits per pixel indexing the palette, default compression, filters and no interlacing
+    let mut header = [width.to_be_bytes(), height.to_be_bytes()].concat();
+    header.extend([8, 3, 0, 0, 0]);
+    png_chunk(&mut out, b"IHDR", &header);
+    png_chunk(&mut out, b"PLTE", &PALETTE.concat());
+    // Number of frames and plays, zero plays loop forever
+    let control = [(frames as u32).to_be_bytes(), 0u32.to_be_bytes()].concat();
+    png_chunk(&mut out, b"acTL", &control);
+
+    // Frame controls and data chunks after the first one share sequence numbers
+    let mut sequence = 0u32;
+    for (i, image) in images.enumerate() {
+        let mut control = [sequence, width, height, 0, 0]
+            .iter()
+            .flat_map(|v| v.to_be_bytes())
+            .collect::<Vec<u8>>();
+        control.extend(delay.to_be_bytes());
+        control.extend(1000u16.to_be_bytes());
+        // Frame is left in place and replaces everything below it
+        control.extend([0, 0]);
+        png_chunk(&mut out, b"fcTL", &control);
+        sequence += 1;
+
+        let data = png_data(&image, width as usize)?;
+        if i == 0 {
+            png_chunk(&mut out, b"IDAT", &data);
+        } else {
+            let data = [&sequence.to_be_bytes()[..], &data].concat();
+            png_chunk(&mut out, b"fdAT", &data);
+            sequence += 1;
+        }
+    }
+
+    png_chunk(&mut out, b"IEND", &[]);
+    Ok(out)
+}
+
+// Compressed rows of palette indices, every row is stored without filter
+fn png_data(pixels: &[u8], width: usize) -> Result<Vec<u8>> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    for row in pixels.chunks(width) {
+        encoder.write_all(&[0])?;
+        encoder.write_all(row)?;
+    }
+    Ok(encoder.finish()?)
+}
+
+fn png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    let mut crc = Crc::new();
+    crc.update(kind);
+    crc.update(data);
+
+    out.extend((data.len() as u32).to_be_bytes());
+    out.extend(kind);
+    out.extend(data);
+    out.extend(crc.sum().to_be_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::ZlibDecoder;
+    use std::io::Read;
+
+    fn frames() -> Vec<Frame> {
+        let mut frame = Frame::new(3, 2);
+        frame.set(0, 0, Style::Origin);
+        let mut second = frame.clone();
+        second.set(2, 1, Style::Trace(10));
+        vec![frame, second]
+    }
+
+    // Decodes GIF image data, reference for the encoder
+    fn unlzw(data: &[u8], min_bits: u8) -> Vec<u8> {
+        let clear = 1usize << min_bits;
+        let mut table: Vec<Vec<u8>> = Vec::new();
+        let mut size = min_bits + 1;
+        let mut previous: Option<Vec<u8>> = None;
+        let (mut pending, mut len, mut bytes) = (0u32, 0, data.iter());
+        let mut out = Vec::new();
+        loop {
+            while len < size {
+                pending |= (*bytes.next().unwrap() as u32) << len;
+                len += 8;
+            }
+            let code = (pending & ((1 << size) - 1)) as usize;
+            pending >>= size;
+            len -= size;
+
+            if code == clear {
+                table = (0..clear as u8).map(|i| vec![i]).collect();
+                table.extend([vec![], vec![]]);
+                size = min_bits + 1;
+                previous = None;
+                continue;
+            }
+            if code == clear + 1 {
+                return out;
+            }
+            let entry = match (table.get(code), &previous) {
+                (Some(entry), _) => entry.clone(),
+                (None, Some(previous)) => [&previous[..], &previous[..1]].concat(),
+                (None, None) => panic!("unknown code {}", code),
+            };
+            if let Some(previous) = previous {
+                table.push([&previous[..], &entry[..1]].concat());
+                if table.len() == 1 << size && size < 12 {
+                    size += 1;
+                }
+            }
+            out.extend(&entry);
+            previous = Some(entry);
+        }
+    }
+
+    #[test]
+    fn test_lzw() {
+        let long: Vec<u8> = (0..20_000u32).map(|i| ((i * i) / 7 % 16) as u8).collect();
+        for indices in [vec![], vec![3], vec![1, 1, 1, 1, 1, 2, 1, 1, 2], long] {
+            assert_eq!(unlzw(&lzw(&indices, 4), 4), indices);
+        }
+    }
+
+    #[test]
+    fn test_pixels() {
+        let frames = frames();
+        assert_eq!(pixels(&frames[0], 1), [1, 0, 0, 0, 0, 0]);
+        assert_eq!(
+            pixels(&frames[1], 2),
+            [1, 1, 0, 0, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 4, 4, 0, 0, 0, 0, 4, 4]
+        );
+    }
+
+    #[test]
+    fn test_gif() {
+        let options = Options {
+            scale: 2,
+            delay: Duration::from_millis(250),
+        };
+        let gif = encode(&frames(), Format::Gif, &options).unwrap();
+        assert!(gif.starts_with(b"GIF89a\x06\x00\x04\x00"));
+        assert_eq!(gif.last(), Some(&0x3b));
+
+        // Images follow graphic control extensions with delay of 25 hundredths
+        let control = [0x21, 0xf9, 0x04, 0x00, 25, 0, 0, 0, 0x2c];
+        let images = gif.windows(control.len()).filter(|w| *w == control).count();
+        assert_eq!(images, 2);
+    }
+
+    #[test]
+    fn test_apng() {
+        let png = encode(&frames(), Format::Apng, &Options::default()).unwrap();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+
+        let mut chunks = Vec::new();
+        let mut rest = &png[8..];
+        while !rest.is_empty() {
+            let len = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+            let (kind, data) = (&rest[4..8], &rest[8..8 + len]);
+            let stored = u32::from_be_bytes(rest[8 + len..12 + len].try_into().unwrap());
+            let mut crc = Crc::new();
+            crc.update(&rest[4..8 + len]);
+            assert_eq!(crc.sum(), stored);
+
+            chunks.push((String::from_utf8(kind.to_vec()).unwrap(), data.to_vec()));
+            rest = &rest[12 + len..];
+        }
+
+        let kinds: Vec<&str> = chunks.iter().map(|(kind, _)| kind.as_str()).collect();
+        assert_eq!(
+            kinds,
+            ["IHDR", "PLTE", "acTL", "fcTL", "IDAT", "fcTL", "fdAT", "IEND"]
+        );
+        assert_eq!(chunks[0].1[..8], [0, 0, 0, 12, 0, 0, 0, 8]);
+        assert_eq!(chunks[2].1, [0, 0, 0, 2, 0, 0, 0, 0]);
+
+        let mut rows = Vec::new();
+        ZlibDecoder::new(&chunks[4].1[..])
+            .read_to_end(&mut rows)
+            .unwrap();
+        assert_eq!(rows.len(), 8 * 13);
+        assert_eq!(rows[..6], [0, 1, 1, 1, 1, 0]);
+        // Sequence number of the second frame's data follows its control chunk
+        assert_eq!(chunks[6].1[..4], [0, 0, 0, 2]);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let options = Options::default();
+        assert_eq!(
+            encode(&[], Format::Gif, &options).unwrap_err().to_string(),
+            "No frames to export"
+        );
+        let frames = [Frame::new(2, 2), Frame::new(3, 2)];
+        assert!(encode(&frames, Format::Apng, &options).is_err());
+
+        let huge = Options {
+            scale: 30_000,
+            ..options
+        };
+        let err = encode(&[Frame::new(3, 1)], Format::Gif, &huge).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "GIF image of 90000x30000 pixels is too large"
+        );
+
+        assert_eq!(Format::from_path("a/day03.GIF").unwrap(), Format::Gif);
+        assert_eq!(Format::from_path("day03.apng").unwrap(), Format::Apng);
+        assert!(Format::from_path("day03.txt").is_err());
+    }
+}
@@ -0,0 +1,607 @@
+// Generates publishable test fixtures from real puzzle inputs. Produced input keeps
+// structure of the original one (number of lines, value ranges, digit counts),
+// but values are re-randomized, so answers have to be recomputed.
+// Also generates random Intcode programs with known results.
+use crate::solution;
+use anyhow::{anyhow, bail, Context, Result};
+use std::collections::{HashMap, HashSet};
+
+// Generated inputs without intersections etc. are retried this many times
+const MAX_ATTEMPTS: usize = 100;
+
+#[derive(Debug)]
+pub struct Fixture {
+    pub input: String,
+    pub part1: String,
+    pub part2: String,
+}
+
+// Small xorshift generator, reproducible by seed
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        // State must never be zero
+        Rng(seed ^ 0x9e37_79b9_7f4a_7c15 | 1)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+
+    // Returns value in inclusive range
+    pub fn range(&mut self, low: u64, high: u64) -> u64 {
+        match (high - low).checked_add(1) {
+            Some(span) => low + self.next_u64() % span,
+            // Range spans all of u64
+            None => self.next_u64(),
+        }
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.range(0, i as u64) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+pub fn anonymize(day: u8, input: &str, seed: u64) -> Result<Fixture> {
+    let solution = solution(day).ok_or(anyhow!("Day {} is not implemented", day))?;
+    let mut rng = Rng::new(seed);
+
+    for _ in 0..MAX_ATTEMPTS {
+        let input = match day {
+            1 => perturb_masses(input, &mut rng)?,
+            3 => randomize_wires(input, &mut rng)?,
+            4 => shift_range(input, &mut rng)?,
+            6 => rename_orbits(input, &mut rng)?,
+            _ => bail!("Day {} inputs can't be anonymized", day),
+        };
+
+        // Some generated inputs have no answer, e.g. wires without intersections
+        if let (Ok(part1), Ok(part2)) = (solution.part1(&input), solution.part2(&input)) {
+            return Ok(Fixture {
+                input,
+                part1,
+                part2,
+            });
+        }
+    }
+
+    bail!(
+        "Couldn't generate solvable input in {} attempts",
+        MAX_ATTEMPTS
+    )
+}
+
+// Shuffles modules and changes every mass by up to 10%
+fn perturb_masses(input: &str, rng: &mut Rng) -> Result<String> {
+    let mut masses = input
+        .lines()
+        .map(|l| l.trim().parse::<u64>())
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to parse module masses")?;
+
+    rng.shuffle(&mut masses);
+    let lines: Vec<String> = masses
+        .iter()
+        .map(|&m| {
+            rng.range(m - m / 10, m.saturating_add(m / 10))
+                .max(1)
+                .to_string()
+        })
+        .collect();
+
+    Ok(lines.join("\n"))
+}
+
+// Builds new wires with the same number of turns and segment lengths within
+// original bounds. Directions alternate between axes like real inputs do.
+fn randomize_wires(input: &str, rng: &mut Rng) -> Result<String> {
+    let mut wires = Vec::new();
+    for line in input.lines() {
+        let lengths = line
+            .split(',')
+            .map(|s| s.trim().get(1..).unwrap_or_default().parse::<u64>())
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to parse wire segments")?;
+        let min = lengths.iter().copied().min().unwrap_or(1).max(1);
+        let max = lengths.iter().copied().max().unwrap_or(1).max(1);
+
+        let mut horizontal = rng.next_u64().is_multiple_of(2);
+        let mut turns = Vec::new();
+        for _ in &lengths {
+            let dir = match (horizontal, rng.next_u64() % 2) {
+                (true, 0) => 'R',
+                (true, _) => 'L',
+                (false, 0) => 'U',
+                (false, _) => 'D',
+            };
+            turns.push(format!("{}{}", dir, rng.range(min, max)));
+            horizontal = !horizontal;
+        }
+        wires.push(turns.join(","));
+    }
+
+    Ok(wires.join("\n"))
+}
+
+// Moves range to random place keeping its width and number of digits
+fn shift_range(input: &str, rng: &mut Rng) -> Result<String> {
+    let (start, end) = input
+        .trim()
+        .split_once('-')
+        .ok_or(anyhow!("Failed to parse range"))?;
+    let digits = start.trim().len() as u32;
+    let (start, end) = (start.trim().parse::<u64>()?, end.trim().parse::<u64>()?);
+    if digits == 0 || start > end {
+        bail!("Invalid range {}-{}", start, end);
+    }
+
+    let low = 10u64.pow(digits - 1);
+    let high = 10u64.pow(digits) - 1;
+    let width = (end - start).min(high - low);
+    let start = rng.range(low, high - width);
+
+    Ok(format!("{}-{}", start, start + width))
+}
+
+// Gives every object new three character name and shuffles lines, so the map keeps
+// its shape and answers. COM, YOU and SAN have a meaning in the puzzle and are kept.
+fn rename_orbits(input: &str, rng: &mut Rng) -> Result<String> {
+    const KEPT: [&str; 3] = ["COM", "YOU", "SAN"];
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+    let mut orbits = Vec::new();
+    for line in input.lines() {
+        let orbit = line
+            .trim()
+            .split_once(')')
+            .ok_or_else(|| anyhow!("Failed to parse orbit {:?}", line))?;
+        orbits.push(orbit);
+    }
+
+    // Every line names at most two objects, so names can't run out
+    if orbits.len() * 2 + KEPT.len() > ALPHABET.len().pow(3) {
+        bail!("Too many objects to rename");
+    }
+    let mut names: HashMap<&str, String> = KEPT.iter().map(|&n| (n, n.to_string())).collect();
+    let mut used: HashSet<String> = names.values().cloned().collect();
+    let mut rename = |object| {
+        names
+            .entry(object)
+            .or_insert_with(|| loop {
+                let name: String = (0..3)
+                    .map(|_| ALPHABET[rng.range(0, ALPHABET.len() as u64 - 1) as usize] as char)
+                    .collect();
+                if used.insert(name.clone()) {
+                    break name;
+                }
+            })
+            .clone()
+    };
+
+    let mut lines: Vec<String> = orbits
+        .into_iter()
+        .map(|(center, object)| format!("{}){}", rename(center), rename(object)))
+        .collect();
+    rng.shuffle(&mut lines);
+
+    Ok(lines.join("\n"))
+}
+
+// Intcode program which is known to halt, with memory and output expected after
+// it's run with the input
+#[derive(Debug, Clone)]
+pub struct IntcodeCase {
+    pub program: Vec<i64>,
+    pub input: Vec<i64>,
+    pub memory: Vec<i64>,
+    pub output: Vec<i64>,
+}
+
+impl IntcodeCase {
+    // Program in puzzle input format
+    pub fn source(&self) -> String {
+        let values: Vec<String> = self.program.iter().map(i64::to_string).collect();
+        values.join(",")
+    }
+}
+
+// Generates program of random instructions using every opcode and parameter mode,
+// followed by halt and data cells. Results are written only to data cells, so code
+// is never modified, and jumps only go forward to instruction boundaries, so the
+// program always halts. Operands are read from code generated so far or data.
+// Expected memory is computed by executing every instruction as it's generated,
+// instructions which would overflow are regenerated. Instructions skipped by
+// a taken jump are generated without being executed.
+pub fn random_intcode(rng: &mut Rng, instructions: usize, data: usize) -> IntcodeCase {
+    // Longest instruction takes 4 cells, code shorter than that is followed by halts
+    let data_start = instructions * 4 + 1;
+    let len = data_start + data.max(1);
+    let mut program = vec![99; len];
+    for cell in &mut program[data_start..] {
+        *cell = rng.range(0, 99) as i64;
+    }
+
+    let mut generator = Generator {
+        rng,
+        memory: program.clone(),
+        program,
+        data_start,
+        ip: 0,
+        relative_base: 0,
+        input: Vec::new(),
+        output: Vec::new(),
+        jump: None,
+    };
+    for _ in 0..instructions {
+        generator.instruction();
+    }
+    generator.finish()
+}
+
+// Jump generated last, its target is known once the following instructions are
+struct Jump {
+    // Cell holding the target
+    cell: usize,
+    // Instructions left before the target
+    left: usize,
+    taken: bool,
+}
+
+struct Generator<'a> {
+    rng: &'a mut Rng,
+    program: Vec<i64>,
+    memory: Vec<i64>,
+    data_start: usize,
+    ip: usize,
+    relative_base: i64,
+    input: Vec<i64>,
+    output: Vec<i64>,
+    jump: Option<Jump>,
+}
+
+impl Generator<'_> {
+    fn instruction(&mut self) {
+        self.land_jump();
+        // Only one jump is in flight, so targets don't have to be nested
+        let opcodes: &[i64] = match self.jump {
+            Some(_) => &[1, 2, 3, 4, 7, 8, 9],
+            None => &[1, 2, 3, 4, 5, 6, 7, 8, 9],
+        };
+        let live = !self.jump.as_ref().is_some_and(|jump| jump.taken);
+
+        let mut cells = None;
+        for _ in 0..MAX_ATTEMPTS {
+            let opcode = opcodes[self.rng.range(0, opcodes.len() as u64 - 1) as usize];
+            let candidate = self.encode(opcode);
+            if !live || self.execute(&candidate) {
+                cells = Some(candidate);
+                break;
+            }
+        }
+        // Output of immediate value can't fail
+        let cells = cells.unwrap_or_else(|| {
+            self.output.push(0);
+            vec![104, 0]
+        });
+
+        for (offset, &cell) in cells.iter().enumerate() {
+            self.program[self.ip + offset] = cell;
+            self.memory[self.ip + offset] = cell;
+        }
+        self.ip += cells.len();
+        if let Some(jump) = &mut self.jump {
+            jump.left = jump.left.saturating_sub(1);
+        }
+    }
+
+    // Sets target of the last jump once it's reached, it's at the halt after the code
+    // if the program ends earlier
+    fn land_jump(&mut self) {
+        if let Some(jump) = self.jump.take_if(|jump| jump.left == 0) {
+            self.program[jump.cell] = self.ip as i64;
+            self.memory[jump.cell] = self.ip as i64;
+        }
+    }
+
+    fn finish(mut self) -> IntcodeCase {
+        if let Some(jump) = &mut self.jump {
+            jump.left = 0;
+        }
+        self.land_jump();
+        IntcodeCase {
+            program: self.program,
+            input: self.input,
+            memory: self.memory,
+            output: self.output,
+        }
+    }
+
+    // Random instruction with the opcode at instruction pointer, parameters are
+    // given random modes
+    fn encode(&mut self, opcode: i64) -> Vec<i64> {
+        let params: &[Param] = match opcode {
+            1 | 2 | 7 | 8 => &[Param::Read, Param::Read, Param::Write],
+            3 => &[Param::Write],
+            4 | 9 => &[Param::Read],
+            _ => &[Param::Read, Param::Target],
+        };
+        let len = params.len() + 1;
+        // Target of a jump is set once it's known, so it can't be read before
+        let unknown = match params {
+            [_, Param::Target] => Some(self.ip + 2),
+            _ => self.jump.as_ref().map(|jump| jump.cell),
+        };
+
+        let mut cells = vec![opcode];
+        for (n, param) in params.iter().enumerate() {
+            let (mode, value) = match param {
+                Param::Read => match self.rng.range(0, 2) {
+                    0 => (0, self.readable(len, unknown) as i64),
+                    1 => (1, self.rng.range(0, 198) as i64 - 99),
+                    _ => {
+                        let address = self.readable(len, unknown);
+                        self.relative(address)
+                    }
+                },
+                Param::Write => {
+                    let address = self
+                        .rng
+                        .range(self.data_start as u64, self.len() as u64 - 1);
+                    match self.rng.range(0, 1) {
+                        0 => (0, address as i64),
+                        _ => self.relative(address as usize),
+                    }
+                }
+                Param::Target => (1, 0),
+            };
+            cells[0] += mode * 10i64.pow(n as u32 + 2);
+            cells.push(value);
+        }
+        cells
+    }
+
+    // Address of code generated so far, cells of the generated instruction included,
+    // or data. Cell whose value isn't known yet is never returned.
+    fn readable(&mut self, len: usize, unknown: Option<usize>) -> usize {
+        let code = self.ip + len;
+        let count = code + self.len() - self.data_start;
+        loop {
+            let i = self.rng.range(0, count as u64 - 1) as usize;
+            let address = if i < code {
+                i
+            } else {
+                self.data_start + i - code
+            };
+            if Some(address) != unknown {
+                return address;
+            }
+        }
+    }
+
+    // Relative mode parameter referring to address, position mode when the offset
+    // doesn't fit
+    fn relative(&self, address: usize) -> (i64, i64) {
+        match (address as i64).checked_sub(self.relative_base) {
+            Some(offset) => (2, offset),
+            None => (0, address as i64),
+        }
+    }
+
+    // Applies effects of instruction at instruction pointer, returns false without
+    // changing anything if it would overflow
+    fn execute(&mut self, cells: &[i64]) -> bool {
+        let opcode = cells[0] % 100;
+        match opcode {
+            1 | 2 | 7 | 8 => {
+                let (a, b) = (self.param(cells, 1), self.param(cells, 2));
+                let value = match opcode {
+                    1 => a.checked_add(b),
+                    2 => a.checked_mul(b),
+                    7 => Some((a < b) as i64),
+                    _ => Some((a == b) as i64),
+                };
+                let Some(value) = value else {
+                    return false;
+                };
+                let dest = self.dest(cells, 3);
+                self.memory[dest] = value;
+            }
+            3 => {
+                let value = self.rng.range(0, 198) as i64 - 99;
+                let dest = self.dest(cells, 1);
+                self.memory[dest] = value;
+                self.input.push(value);
+            }
+            4 => {
+                let value = self.param(cells, 1);
+                self.output.push(value);
+            }
+            5 | 6 => {
+                let condition = self.param(cells, 1);
+                self.jump = Some(Jump {
+                    cell: self.ip + 2,
+                    left: self.rng.range(0, 3) as usize,
+                    taken: (condition != 0) == (opcode == 5),
+                });
+            }
+            _ => match self.relative_base.checked_add(self.param(cells, 1)) {
+                Some(base) => self.relative_base = base,
+                None => return false,
+            },
+        }
+        true
+    }
+
+    fn param(&self, cells: &[i64], n: usize) -> i64 {
+        match mode(cells[0], n) {
+            1 => cells[n],
+            _ => {
+                // Instruction may read its own cells
+                let address = self.dest(cells, n);
+                match address.checked_sub(self.ip) {
+                    Some(offset) if offset < cells.len() => cells[offset],
+                    _ => self.memory[address],
+                }
+            }
+        }
+    }
+
+    fn dest(&self, cells: &[i64], n: usize) -> usize {
+        match mode(cells[0], n) {
+            2 => (self.relative_base + cells[n]) as usize,
+            _ => cells[n] as usize,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.program.len()
+    }
+}
+
+enum Param {
+    Read,
+    Write,
+    // Jump target in immediate mode
+    Target,
+}
+
+fn mode(instruction: i64, n: usize) -> i64 {
+    instruction / 10i64.pow(n as u32 + 1) % 10
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::computer::{IntcodeComputer, Memory, StepResult};
+    use crate::Solution;
+
+    #[test]
+    fn test_random_intcode() {
+        let mut rng = Rng::new(3);
+        let mut opcodes = HashSet::new();
+        let mut modes = HashSet::new();
+        for _ in 0..200 {
+            let instructions = rng.range(0, 30) as usize;
+            let data = rng.range(1, 10) as usize;
+            let case = random_intcode(&mut rng, instructions, data);
+            assert_eq!(case.program.len(), instructions * 4 + 1 + data);
+
+            let mut computer = IntcodeComputer::new(&case.source()).unwrap();
+            for &value in &case.input {
+                computer.push_input(value);
+            }
+            let mut output = Vec::new();
+            // Jumps go only forward, so every instruction runs at most once
+            for _ in 0..=instructions {
+                let instruction = computer.ram().read(computer.ip()).unwrap();
+                opcodes.insert(instruction % 100);
+                modes.extend([100, 1000, 10000].map(|digit| instruction / digit % 10));
+                match computer.step().unwrap() {
+                    Some(StepResult::Output(value)) => output.push(value),
+                    Some(StepResult::Halted) => break,
+                    event => assert_eq!(event, None, "program {}", case.source()),
+                }
+            }
+            assert!(computer.is_halted(), "program {}", case.source());
+            assert_eq!(output, case.output);
+
+            let ram = computer.ram();
+            let memory: Vec<i64> = (0..ram.len()).map(|a| ram.read(a).unwrap()).collect();
+            assert_eq!(memory, case.memory);
+        }
+
+        assert_eq!(opcodes, HashSet::from([1, 2, 3, 4, 5, 6, 7, 8, 9, 99]));
+        assert_eq!(modes, HashSet::from([0, 1, 2]));
+    }
+
+    #[test]
+    fn test_rng_range() {
+        let mut rng = Rng::new(42);
+        for _ in 0..1000 {
+            let v = rng.range(3, 7);
+            assert!((3..=7).contains(&v));
+        }
+        assert_eq!(rng.range(9, 9), 9);
+        rng.range(0, u64::MAX);
+    }
+
+    #[test]
+    fn test_anonymize_masses() {
+        let fixture = anonymize(1, "12\n14\n1969\n100756", 1).unwrap();
+        assert_eq!(fixture.input.lines().count(), 4);
+        assert_eq!(
+            crate::day01::Day01.part1(&fixture.input).unwrap(),
+            fixture.part1
+        );
+    }
+
+    #[test]
+    fn test_perturb_huge_masses() {
+        let mut rng = Rng::new(11);
+        let input = format!("{}\n{}", u64::MAX, u64::MAX / 10 * 9);
+        for _ in 0..100 {
+            let masses = perturb_masses(&input, &mut rng).unwrap();
+            for mass in masses.lines() {
+                assert!(mass.parse::<u64>().unwrap() >= u64::MAX / 10 * 8);
+            }
+        }
+    }
+
+    #[test]
+    fn test_anonymize_wires() {
+        let input = "R75,D30,R83,U83,L12,D49,R71,U7,L72\nU62,R66,U55,R34,D71,R55,D58,R83";
+        let fixture = anonymize(3, input, 7).unwrap();
+        assert_ne!(fixture.input, input);
+
+        let lines: Vec<&str> = fixture.input.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].split(',').count(), 9);
+        assert_eq!(lines[1].split(',').count(), 8);
+    }
+
+    #[test]
+    fn test_anonymize_range() {
+        let fixture = anonymize(4, "178416-676461", 3).unwrap();
+        let (start, end) = fixture.input.split_once('-').unwrap();
+        assert_eq!(start.len(), 6);
+        assert_eq!(end.len(), 6);
+        assert_eq!(
+            end.parse::<u64>().unwrap() - start.parse::<u64>().unwrap(),
+            676461 - 178416
+        );
+    }
+
+    #[test]
+    fn test_anonymize_orbits() {
+        let input = "COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L\nK)YOU\nI)SAN";
+        let fixture = anonymize(6, input, 5).unwrap();
+        assert_eq!(fixture.part1, "54");
+        assert_eq!(fixture.part2, "4");
+
+        let lines: Vec<&str> = fixture.input.lines().collect();
+        assert_eq!(lines.len(), 13);
+        for kept in ["COM)", ")YOU", ")SAN"] {
+            assert!(fixture.input.contains(kept), "{:?} is kept", kept);
+        }
+        for object in ["B", "K", "L"] {
+            assert!(!lines
+                .iter()
+                .any(|line| line.split(')').any(|o| o == object)));
+        }
+    }
+
+    #[test]
+    fn test_anonymize_unsupported() {
+        assert!(anonymize(2, "1,0,0,0,99", 0).is_err());
+        assert!(anonymize(25, "", 0).is_err());
+    }
+}
@@ -0,0 +1,490 @@
+// Points and axis aligned segments on integer grid
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Point {
+    pub const ORIGIN: Point = Point { x: 0, y: 0 };
+}
+
+pub fn manhattan_distance(pt1: Point, pt2: Point) -> i32 {
+    (pt1.x - pt2.x).abs() + (pt1.y - pt2.y).abs()
+}
+
+// Horizontal or vertical segment, both ends are inclusive
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Line {
+    pub p1: Point,
+    pub p2: Point,
+}
+
+impl Line {
+    // Segment of a single point is both horizontal and vertical
+    pub fn is_horizontal(&self) -> bool {
+        self.p1.y == self.p2.y
+    }
+
+    pub fn is_vertical(&self) -> bool {
+        self.p1.x == self.p2.x
+    }
+
+    pub fn contains(&self, pt: Point) -> bool {
+        is_value_in_range(self.p1.x, self.p2.x, pt.x)
+            && is_value_in_range(self.p1.y, self.p2.y, pt.y)
+    }
+
+    // Returns manhattan distance between two points of line
+    pub fn distance(&self) -> u32 {
+        manhattan_distance(self.p1, self.p2) as u32
+    }
+
+    // Returns point reached after walking given number of steps from p1
+    pub fn point_at(&self, steps: u32) -> Point {
+        let steps = steps as i32;
+        Point {
+            x: self.p1.x + (self.p2.x - self.p1.x).signum() * steps,
+            y: self.p1.y + (self.p2.y - self.p1.y).signum() * steps,
+        }
+    }
+
+    // Iterates over every point from p1 to p2
+    pub fn points(&self) -> impl Iterator<Item = Point> + '_ {
+        (0..=self.distance()).map(|steps| self.point_at(steps))
+    }
+
+    // Crossing point of perpendicular segments, collinear ones are handled by overlap()
+    pub fn intersection(&self, other: &Line) -> Option<Point> {
+        let (l1, l2) = (self, other);
+        if l1.is_vertical() && !l2.is_vertical() {
+            // l1 is vertical and l2 is horizontal
+            if is_value_in_range(l1.p1.y, l1.p2.y, l2.p1.y)
+                && is_value_in_range(l2.p1.x, l2.p2.x, l1.p1.x)
+            {
+                return Some(Point {
+                    x: l1.p1.x,
+                    ..l2.p1
+                });
+            }
+        } else if l1.is_horizontal() && !l2.is_horizontal() {
+            // l1 is horizontal and l2 is vertical
+            if is_value_in_range(l1.p1.x, l1.p2.x, l2.p1.x)
+                && is_value_in_range(l2.p1.y, l2.p2.y, l1.p1.y)
+            {
+                return Some(Point {
+                    x: l2.p1.x,
+                    ..l1.p1
+                });
+            }
+        }
+        None
+    }
+
+    // Common part of segments lying on the same line, it goes in the positive direction
+    pub fn overlap(&self, other: &Line) -> Option<Line> {
+        let (a, b) = (self.ordered(), other.ordered());
+        let (p1, p2) = if a.is_horizontal() && b.is_horizontal() && a.p1.y == b.p1.y {
+            (
+                Point {
+                    x: a.p1.x.max(b.p1.x),
+                    y: a.p1.y,
+                },
+                Point {
+                    x: a.p2.x.min(b.p2.x),
+                    y: a.p1.y,
+                },
+            )
+        } else if a.is_vertical() && b.is_vertical() && a.p1.x == b.p1.x {
+            (
+                Point {
+                    x: a.p1.x,
+                    y: a.p1.y.max(b.p1.y),
+                },
+                Point {
+                    x: a.p1.x,
+                    y: a.p2.y.min(b.p2.y),
+                },
+            )
+        } else {
+            return None;
+        };
+
+        (p1.x <= p2.x && p1.y <= p2.y).then_some(Line { p1, p2 })
+    }
+
+    // Same segment with p1 not greater than p2
+    fn ordered(&self) -> Line {
+        if (self.p1.x, self.p1.y) <= (self.p2.x, self.p2.y) {
+            *self
+        } else {
+            Line {
+                p1: self.p2,
+                p2: self.p1,
+            }
+        }
+    }
+}
+
+// Smallest rectangle containing every point of wire, corners are inclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoundingBox {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl BoundingBox {
+    pub fn new(pt: Point) -> BoundingBox {
+        BoundingBox { min: pt, max: pt }
+    }
+
+    // Grows the box so it contains the point as well
+    pub fn include(&mut self, pt: Point) {
+        self.min.x = self.min.x.min(pt.x);
+        self.min.y = self.min.y.min(pt.y);
+        self.max.x = self.max.x.max(pt.x);
+        self.max.y = self.max.y.max(pt.y);
+    }
+
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        let mut bbox = *self;
+        bbox.include(other.min);
+        bbox.include(other.max);
+        bbox
+    }
+
+    pub fn width(&self) -> u32 {
+        (self.max.x - self.min.x) as u32 + 1
+    }
+
+    pub fn height(&self) -> u32 {
+        (self.max.y - self.min.y) as u32 + 1
+    }
+}
+
+// Point shared by two paths with the fewest steps along each of them to reach it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crossing {
+    pub point: Point,
+    pub steps: (u64, u64),
+}
+
+// Segment of a path with number of steps taken before its first point
+#[derive(Debug, Clone, Copy)]
+struct Walked {
+    line: Line,
+    steps: u64,
+}
+
+impl Walked {
+    fn steps_to(&self, pt: Point) -> u64 {
+        self.steps + manhattan_distance(self.line.p1, pt) as u64
+    }
+}
+
+// Splits connected segments into horizontal and vertical ones with steps along the path
+fn walk(path: &[Line]) -> (Vec<Walked>, Vec<Walked>) {
+    let (mut horizontal, mut vertical) = (Vec::new(), Vec::new());
+    let mut steps = 0;
+    for &line in path {
+        let walked = Walked { line, steps };
+        if line.is_horizontal() {
+            horizontal.push(walked);
+        } else {
+            vertical.push(walked);
+        }
+        steps += line.distance() as u64;
+    }
+    (horizontal, vertical)
+}
+
+// Returns every point shared by two paths of connected segments, ordered by steps along
+// the first path. Perpendicular segments are found with a sweep over x, so it takes
+// O((n + k) log n) for n segments and k crossings instead of checking every pair.
+pub fn crossings(path1: &[Line], path2: &[Line]) -> Vec<Crossing> {
+    let (horizontal1, vertical1) = walk(path1);
+    let (horizontal2, vertical2) = walk(path2);
+
+    // Path may visit a point several times, only the first visit counts
+    let mut found: HashMap<Point, (u64, u64)> = HashMap::new();
+    let mut add = |point: Point, steps1: u64, steps2: u64| {
+        let steps = found.entry(point).or_insert((steps1, steps2));
+        steps.0 = steps.0.min(steps1);
+        steps.1 = steps.1.min(steps2);
+    };
+
+    sweep(&horizontal1, &vertical2, &mut add);
+    sweep(&horizontal2, &vertical1, |pt, s2, s1| add(pt, s1, s2));
+    collinear(&horizontal1, &horizontal2, |l| l.p1.y, &mut add);
+    collinear(&vertical1, &vertical2, |l| l.p1.x, &mut add);
+
+    let mut crossings: Vec<Crossing> = found
+        .into_iter()
+        .map(|(point, steps)| Crossing { point, steps })
+        .collect();
+    crossings.sort_unstable_by_key(|c| (c.steps, c.point.x, c.point.y));
+    crossings
+}
+
+// Sweep events at the same x are ordered so segments touching by ends are found too
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Event {
+    Start,
+    Check,
+    End,
+}
+
+// Reports points where horizontal segments cross vertical ones. Horizontal segments
+// are kept ordered by y while sweep passes over them, so every vertical segment only
+// looks at those within its span.
+fn sweep(horizontal: &[Walked], vertical: &[Walked], mut found: impl FnMut(Point, u64, u64)) {
+    let mut events = Vec::with_capacity(horizontal.len() * 2 + vertical.len());
+    for (i, h) in horizontal.iter().enumerate() {
+        events.push((h.line.p1.x.min(h.line.p2.x), Event::Start, i));
+        events.push((h.line.p1.x.max(h.line.p2.x), Event::End, i));
+    }
+    for (i, v) in vertical.iter().enumerate() {
+        events.push((v.line.p1.x, Event::Check, i));
+    }
+    events.sort_unstable();
+
+    let mut active: BTreeMap<i32, Vec<usize>> = BTreeMap::new();
+    for (x, event, i) in events {
+        match event {
+            Event::Start => active.entry(horizontal[i].line.p1.y).or_default().push(i),
+            Event::Check => {
+                let v = &vertical[i];
+                let (y1, y2) = (v.line.p1.y.min(v.line.p2.y), v.line.p1.y.max(v.line.p2.y));
+                for (&y, segments) in active.range(y1..=y2) {
+                    let pt = Point { x, y };
+                    for &h in segments {
+                        found(pt, horizontal[h].steps_to(pt), v.steps_to(pt));
+                    }
+                }
+            }
+            Event::End => {
+                let y = horizontal[i].line.p1.y;
+                let segments = active.get_mut(&y).unwrap();
+                let pos = segments.iter().position(|&h| h == i).unwrap();
+                segments.swap_remove(pos);
+                if segments.is_empty() {
+                    active.remove(&y);
+                }
+            }
+        }
+    }
+}
+
+// Reports every point of overlapping segments which lie on the same line
+fn collinear(
+    segments1: &[Walked],
+    segments2: &[Walked],
+    line_of: fn(&Line) -> i32,
+    found: &mut impl FnMut(Point, u64, u64),
+) {
+    let mut by_line: HashMap<i32, Vec<&Walked>> = HashMap::new();
+    for w in segments2 {
+        by_line.entry(line_of(&w.line)).or_default().push(w);
+    }
+
+    for a in segments1 {
+        for b in by_line.get(&line_of(&a.line)).into_iter().flatten() {
+            if let Some(overlap) = a.line.overlap(&b.line) {
+                for pt in overlap.points() {
+                    found(pt, a.steps_to(pt), b.steps_to(pt));
+                }
+            }
+        }
+    }
+}
+
+fn is_value_in_range(start: i32, end: i32, val: i32) -> bool {
+    if start > end {
+        return is_value_in_range(end, start, val);
+    }
+    start <= val && val <= end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_line(p1: (i32, i32), p2: (i32, i32)) -> Line {
+        Line {
+            p1: Point { x: p1.0, y: p1.1 },
+            p2: Point { x: p2.0, y: p2.1 },
+        }
+    }
+
+    // Connected segments going through the points
+    fn build_path(points: &[(i32, i32)]) -> Vec<Line> {
+        points.windows(2).map(|p| build_line(p[0], p[1])).collect()
+    }
+
+    #[test]
+    fn test_lines_intersection() {
+        let l1 = build_line((3, 5), (3, 3));
+        let l2 = build_line((6, 3), (2, 3));
+        assert_eq!(l1.intersection(&l2), Some(Point { x: 3, y: 3 }));
+        assert_eq!(l2.intersection(&l1), Some(Point { x: 3, y: 3 }));
+
+        let l1 = build_line((8, 0), (8, 5));
+        let l2 = build_line((0, 7), (6, 7));
+        assert_eq!(l1.intersection(&l2), None);
+
+        let l1 = build_line((8, 5), (3, 5));
+        let l2 = build_line((6, 7), (6, 3));
+        assert_eq!(l1.intersection(&l2), Some(Point { x: 6, y: 5 }));
+
+        let l1 = build_line((0, 0), (8, 0));
+        let l2 = build_line((0, 0), (0, 7));
+        assert_eq!(l1.intersection(&l2), Some(Point { x: 0, y: 0 }));
+    }
+
+    #[test]
+    fn test_lines_overlap() {
+        let l1 = build_line((0, 2), (8, 2));
+        let l2 = build_line((10, 2), (5, 2));
+        assert_eq!(l1.overlap(&l2), Some(build_line((5, 2), (8, 2))));
+        assert_eq!(l2.overlap(&l1), Some(build_line((5, 2), (8, 2))));
+
+        let l1 = build_line((1, -4), (1, 0));
+        let l2 = build_line((1, 0), (1, 3));
+        assert_eq!(l1.overlap(&l2), Some(build_line((1, 0), (1, 0))));
+        assert_eq!(l1.overlap(&build_line((1, 1), (1, 3))), None);
+        assert_eq!(l1.overlap(&build_line((2, -4), (2, 0))), None);
+        assert_eq!(l1.overlap(&build_line((0, -2), (3, -2))), None);
+        assert_eq!(l1.intersection(&l2), None);
+    }
+
+    #[test]
+    fn test_line_points() {
+        let line = build_line((2, 1), (-1, 1));
+        let points: Vec<Point> = line.points().collect();
+        assert_eq!(points.len(), 4);
+        assert_eq!(points[0], line.p1);
+        assert_eq!(points[3], line.p2);
+        assert!(points.iter().all(|&pt| line.contains(pt)));
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let mut bbox = BoundingBox::new(Point::ORIGIN);
+        bbox.include(Point { x: 3, y: -2 });
+        assert_eq!((bbox.width(), bbox.height()), (4, 3));
+
+        let other = BoundingBox::new(Point { x: -1, y: 5 });
+        let union = bbox.union(&other);
+        assert_eq!(union.min, Point { x: -1, y: -2 });
+        assert_eq!(union.max, Point { x: 3, y: 5 });
+    }
+
+    #[test]
+    fn test_crossings() {
+        let path1 = build_path(&[(0, 0), (8, 0), (8, 5), (3, 5), (3, 2)]);
+        let path2 = build_path(&[(0, 0), (0, 7), (6, 7), (6, 3), (2, 3)]);
+        let points = |crossings: Vec<Crossing>| -> Vec<(i32, i32, u64, u64)> {
+            crossings
+                .iter()
+                .map(|c| (c.point.x, c.point.y, c.steps.0, c.steps.1))
+                .collect()
+        };
+
+        assert_eq!(
+            points(crossings(&path1, &path2)),
+            [(0, 0, 0, 0), (6, 5, 15, 15), (3, 3, 20, 20)]
+        );
+        assert_eq!(
+            points(crossings(&path2, &path1)),
+            [(0, 0, 0, 0), (6, 5, 15, 15), (3, 3, 20, 20)]
+        );
+    }
+
+    #[test]
+    fn test_crossings_collinear() {
+        // Paths share part of a segment in opposite directions
+        let path1 = build_path(&[(0, 0), (4, 0), (4, 2), (1, 2)]);
+        let path2 = build_path(&[(0, 0), (0, 2), (3, 2), (3, 5)]);
+        let found = crossings(&path1, &path2);
+        let shared: Vec<Point> = found.iter().map(|c| c.point).collect();
+        assert_eq!(
+            shared,
+            [
+                Point::ORIGIN,
+                Point { x: 3, y: 2 },
+                Point { x: 2, y: 2 },
+                Point { x: 1, y: 2 },
+            ]
+        );
+        assert_eq!(found[1].steps, (7, 5));
+        assert_eq!(found[3].steps, (9, 3));
+
+        // Only the first visit counts when path comes back to the same point
+        let path1 = build_path(&[(0, 0), (4, 0), (4, 2), (2, 2), (2, -2)]);
+        let path2 = build_path(&[(0, 0), (0, -1), (2, -1), (2, 0)]);
+        let steps: Vec<(Point, (u64, u64))> = crossings(&path1, &path2)
+            .iter()
+            .map(|c| (c.point, c.steps))
+            .collect();
+        assert_eq!(
+            steps,
+            [
+                (Point::ORIGIN, (0, 0)),
+                (Point { x: 2, y: 0 }, (2, 4)),
+                (Point { x: 2, y: -1 }, (11, 3)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_crossings_match_pairwise() {
+        // Pseudo random spiral-ish paths compared with checking every pair of points
+        let mut seed = 7u32;
+        let mut next = || {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            (seed >> 16) as i32 % 9 - 4
+        };
+        let mut path = |horizontal_first: bool| {
+            let mut pt = Point::ORIGIN;
+            let mut points = vec![(0, 0)];
+            for i in 0..40 {
+                if (i % 2 == 0) == horizontal_first {
+                    pt.x += next();
+                } else {
+                    pt.y += next();
+                }
+                points.push((pt.x, pt.y));
+            }
+            build_path(&points)
+        };
+        let (path1, path2) = (path(true), path(false));
+
+        let visits = |path: &[Line]| {
+            let mut visits: HashMap<Point, u64> = HashMap::new();
+            let mut steps = 0;
+            for line in path {
+                for (i, pt) in line.points().enumerate() {
+                    visits.entry(pt).or_insert(steps + i as u64);
+                }
+                steps += line.distance() as u64;
+            }
+            visits
+        };
+        let (visits1, visits2) = (visits(&path1), visits(&path2));
+        let mut expected: Vec<Crossing> = visits1
+            .iter()
+            .filter_map(|(pt, &s1)| {
+                visits2.get(pt).map(|&s2| Crossing {
+                    point: *pt,
+                    steps: (s1, s2),
+                })
+            })
+            .collect();
+        expected.sort_unstable_by_key(|c| (c.steps, c.point.x, c.point.y));
+
+        assert!(expected.len() > 1);
+        assert_eq!(crossings(&path1, &path2), expected);
+    }
+}
@@ -0,0 +1,239 @@
+// Graphs and trees with small copyable nodes, like names borrowed from input
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+// Adjacency map of directed edges, undirected ones are stored in both directions
+#[derive(Debug, Clone)]
+pub struct Graph<N> {
+    adjacent: HashMap<N, Vec<N>>,
+}
+
+impl<N: Copy + Eq + Hash> Default for Graph<N> {
+    fn default() -> Self {
+        Graph {
+            adjacent: HashMap::new(),
+        }
+    }
+}
+
+impl<N: Copy + Eq + Hash> Graph<N> {
+    pub fn new() -> Graph<N> {
+        Graph::default()
+    }
+
+    pub fn add_node(&mut self, node: N) {
+        self.adjacent.entry(node).or_default();
+    }
+
+    pub fn add_edge(&mut self, from: N, to: N) {
+        self.adjacent.entry(from).or_default().push(to);
+        self.add_node(to);
+    }
+
+    pub fn add_undirected_edge(&mut self, a: N, b: N) {
+        self.add_edge(a, b);
+        self.add_edge(b, a);
+    }
+
+    pub fn contains(&self, node: N) -> bool {
+        self.adjacent.contains_key(&node)
+    }
+
+    // Nodes reachable by a single edge, in the order edges were added
+    pub fn neighbors(&self, node: N) -> &[N] {
+        self.adjacent.get(&node).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = N> + '_ {
+        self.adjacent.keys().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.adjacent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.adjacent.is_empty()
+    }
+
+    // Number of edges on the shortest way from start to every reachable node
+    pub fn distances(&self, start: N) -> HashMap<N, usize> {
+        let mut distances = HashMap::from([(start, 0)]);
+        let mut queue = VecDeque::from([start]);
+        while let Some(node) = queue.pop_front() {
+            let distance = distances[&node] + 1;
+            for &next in self.neighbors(node) {
+                distances.entry(next).or_insert_with(|| {
+                    queue.push_back(next);
+                    distance
+                });
+            }
+        }
+        distances
+    }
+
+    // Nodes on one of the shortest paths from start to goal, both ends included
+    pub fn shortest_path(&self, start: N, goal: N) -> Option<Vec<N>> {
+        let mut previous = HashMap::from([(start, start)]);
+        let mut queue = VecDeque::from([start]);
+        while let Some(node) = queue.pop_front() {
+            if node == goal {
+                let mut path = vec![goal];
+                let mut node = goal;
+                while node != start {
+                    node = previous[&node];
+                    path.push(node);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for &next in self.neighbors(node) {
+                previous.entry(next).or_insert_with(|| {
+                    queue.push_back(next);
+                    node
+                });
+            }
+        }
+        None
+    }
+}
+
+// Rooted trees given by parent of every node but roots
+#[derive(Debug, Clone)]
+pub struct Tree<N> {
+    parents: HashMap<N, N>,
+}
+
+impl<N: Copy + Eq + Hash> Default for Tree<N> {
+    fn default() -> Self {
+        Tree {
+            parents: HashMap::new(),
+        }
+    }
+}
+
+impl<N: Copy + Eq + Hash> Tree<N> {
+    pub fn new() -> Tree<N> {
+        Tree::default()
+    }
+
+    // Returns previous parent of the node, which is replaced
+    pub fn set_parent(&mut self, node: N, parent: N) -> Option<N> {
+        self.parents.insert(node, parent)
+    }
+
+    pub fn parent(&self, node: N) -> Option<N> {
+        self.parents.get(&node).copied()
+    }
+
+    // Iterates from parent of the node up to its root. Parents may form a cycle,
+    // so iteration stops after every node with a parent was visited once.
+    pub fn ancestors(&self, node: N) -> impl Iterator<Item = N> + '_ {
+        let mut node = node;
+        (0..self.parents.len()).map_while(move |_| {
+            node = self.parent(node)?;
+            Some(node)
+        })
+    }
+
+    // Number of ancestors of every node, None when parents form a cycle
+    pub fn depths(&self) -> Option<HashMap<N, usize>> {
+        let children = self.children();
+        let mut depths = HashMap::new();
+        for root in children.nodes().filter(|&node| self.parent(node).is_none()) {
+            depths.extend(children.distances(root));
+        }
+
+        (depths.len() == children.len()).then_some(depths)
+    }
+
+    // Closest node which both nodes descend from, the node itself counts as well
+    pub fn common_ancestor(&self, a: N, b: N) -> Option<N> {
+        let mut path: Vec<N> = [a].into_iter().chain(self.ancestors(a)).collect();
+        path.reverse();
+        let other: Vec<N> = [b].into_iter().chain(self.ancestors(b)).collect();
+
+        let shared = path
+            .iter()
+            .zip(other.iter().rev())
+            .take_while(|(x, y)| x == y)
+            .count();
+        shared.checked_sub(1).map(|i| path[i])
+    }
+
+    // Graph with edges going from parents to their children
+    pub fn children(&self) -> Graph<N> {
+        let mut graph = Graph::new();
+        for (&node, &parent) in &self.parents {
+            graph.add_edge(parent, node);
+        }
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_graph() {
+        let mut graph = Graph::new();
+        for (a, b) in [(1, 2), (2, 3), (3, 4), (1, 5), (5, 4), (6, 7)] {
+            graph.add_undirected_edge(a, b);
+        }
+        graph.add_node(8);
+
+        assert_eq!(graph.len(), 8);
+        assert_eq!(graph.neighbors(1), [2, 5]);
+        assert_eq!(graph.neighbors(8), []);
+        assert_eq!(graph.neighbors(9), []);
+        assert!(graph.contains(8) && !graph.contains(9));
+
+        let distances = graph.distances(1);
+        assert_eq!(distances.len(), 5);
+        assert_eq!((distances[&1], distances[&3], distances[&4]), (0, 2, 2));
+
+        assert_eq!(graph.shortest_path(1, 4), Some(vec![1, 5, 4]));
+        assert_eq!(graph.shortest_path(3, 3), Some(vec![3]));
+        assert_eq!(graph.shortest_path(1, 7), None);
+
+        let mut directed = Graph::new();
+        directed.add_edge("a", "b");
+        assert_eq!(directed.shortest_path("a", "b"), Some(vec!["a", "b"]));
+        assert_eq!(directed.shortest_path("b", "a"), None);
+    }
+
+    #[test]
+    fn test_tree() {
+        let mut tree = Tree::new();
+        for (node, parent) in [("B", "A"), ("C", "B"), ("D", "B"), ("E", "D"), ("Y", "X")] {
+            assert_eq!(tree.set_parent(node, parent), None);
+        }
+
+        assert_eq!(tree.parent("E"), Some("D"));
+        assert_eq!(tree.parent("A"), None);
+        assert_eq!(tree.ancestors("E").collect::<Vec<_>>(), ["D", "B", "A"]);
+        assert_eq!(tree.ancestors("A").count(), 0);
+
+        let depths = tree.depths().unwrap();
+        assert_eq!(depths.len(), 7);
+        assert_eq!((depths[&"A"], depths[&"E"], depths[&"Y"]), (0, 3, 1));
+
+        assert_eq!(tree.common_ancestor("C", "E"), Some("B"));
+        assert_eq!(tree.common_ancestor("E", "D"), Some("D"));
+        assert_eq!(tree.common_ancestor("E", "Y"), None);
+        assert_eq!(tree.children().neighbors("X"), ["Y"]);
+    }
+
+    #[test]
+    fn test_tree_cycle() {
+        let mut tree = Tree::new();
+        tree.set_parent(1, 0);
+        tree.set_parent(2, 3);
+        tree.set_parent(3, 2);
+
+        assert_eq!(tree.depths(), None);
+        assert_eq!(tree.ancestors(2).collect::<Vec<_>>(), [3, 2, 3]);
+        assert_eq!(tree.set_parent(1, 4), Some(0));
+    }
+}
@@ -0,0 +1,275 @@
+use anyhow::{Context, Result};
+use std::borrow::Cow;
+use std::env;
+use std::fs;
+use std::io::{self, Read, Seek};
+
+// Directory with inputs named like input03.txt, can be changed with AOC_INPUT_DIR
+const DEFAULT_INPUT_DIR: &str = "data";
+
+// Files larger than this are mapped into memory when mmap feature is enabled
+#[cfg(feature = "mmap")]
+const MMAP_THRESHOLD: u64 = 16 * 1024 * 1024;
+
+// Path which reads input from standard input
+pub const STDIN: &str = "-";
+
+// Compressed files are recognized by magic bytes, not by extension
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+// Puzzle input loaded from file
+pub enum Input {
+    Owned(String),
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap),
+    // Compiled into binary with embedded-inputs feature
+    #[cfg(feature = "embedded-inputs")]
+    Embedded(&'static str),
+}
+
+impl Input {
+    // Gzip and zstd (with zstd feature) compressed files are decompressed
+    pub fn read(path: &str) -> Result<Input> {
+        if path == STDIN {
+            return read_stdin().context("Failed to read standard input");
+        }
+
+        let mut file = fs::File::open(path).with_context(|| format!("Failed to open {}", path))?;
+        let mut magic = Vec::new();
+        file.by_ref()
+            .take(ZSTD_MAGIC.len() as u64)
+            .read_to_end(&mut magic)
+            .with_context(|| format!("Failed to read {}", path))?;
+        file.rewind()?;
+
+        if magic.starts_with(GZIP_MAGIC) || magic.starts_with(ZSTD_MAGIC) {
+            return decompress(file, &magic)
+                .with_context(|| format!("Failed to decompress {}", path));
+        }
+
+        #[cfg(feature = "mmap")]
+        {
+            let len = file.metadata()?.len();
+            if len >= MMAP_THRESHOLD {
+                // SAFETY: input files are not expected to be modified while solving
+                let map = unsafe { memmap2::Mmap::map(&file) }
+                    .with_context(|| format!("Failed to map {}", path))?;
+                std::str::from_utf8(&map).with_context(|| format!("{} is not UTF-8", path))?;
+                return Ok(Input::Mapped(map));
+            }
+        }
+
+        let mut content = String::new();
+        file.read_to_string(&mut content)
+            .with_context(|| format!("Failed to read {}", path))?;
+        Ok(Input::Owned(content))
+    }
+
+    // Reads input of the day, falls back to embedded one when the file doesn't exist
+    #[cfg_attr(not(feature = "embedded-inputs"), allow(unused_variables))]
+    pub fn read_day(day: u8, path: &str) -> Result<Input> {
+        #[cfg(feature = "embedded-inputs")]
+        if path != STDIN && !std::path::Path::new(path).exists() {
+            if let Some(input) = embedded(day) {
+                return Ok(Input::Embedded(input));
+            }
+        }
+
+        Input::read(path)
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Input::Owned(s) => s,
+            // SAFETY: mapped content was validated to be UTF-8 in read()
+            #[cfg(feature = "mmap")]
+            Input::Mapped(map) => unsafe { std::str::from_utf8_unchecked(map) },
+            #[cfg(feature = "embedded-inputs")]
+            Input::Embedded(s) => s,
+        }
+    }
+
+    // Iterates over lines without copying them
+    pub fn lines(&self) -> std::str::Lines<'_> {
+        self.as_str().lines()
+    }
+
+    pub fn normalized(&self) -> Cow<'_, str> {
+        normalize(self.as_str())
+    }
+}
+
+fn read_stdin() -> Result<Input> {
+    let mut content = Vec::new();
+    io::stdin().lock().read_to_end(&mut content)?;
+    if content.starts_with(GZIP_MAGIC) || content.starts_with(ZSTD_MAGIC) {
+        return decompress(&content[..], &content);
+    }
+    Ok(Input::Owned(String::from_utf8(content)?))
+}
+
+fn decompress(file: impl Read, magic: &[u8]) -> Result<Input> {
+    let file = io::BufReader::new(file);
+    let mut content = String::new();
+    if magic.starts_with(GZIP_MAGIC) {
+        // Concatenated gzip members are read as one stream like gunzip does
+        flate2::read::MultiGzDecoder::new(file).read_to_string(&mut content)?;
+    } else {
+        read_zstd(file, &mut content)?;
+    }
+    Ok(Input::Owned(content))
+}
+
+#[cfg(feature = "zstd")]
+fn read_zstd(file: impl Read, content: &mut String) -> Result<()> {
+    let mut decoder = ruzstd::decoding::StreamingDecoder::new(file)?;
+    decoder.read_to_string(content)?;
+    Ok(())
+}
+
+#[cfg(not(feature = "zstd"))]
+fn read_zstd(_file: impl Read, _content: &mut String) -> Result<()> {
+    anyhow::bail!("zstd compressed inputs require zstd feature")
+}
+
+// Returns path to input file of the day in default input directory
+pub fn default_path(day: u8) -> String {
+    let dir = env::var("AOC_INPUT_DIR").unwrap_or_else(|_| DEFAULT_INPUT_DIR.to_string());
+    path_in(&dir, day)
+}
+
+// Returns path to input file of the day in given directory
+pub fn path_in(dir: &str, day: u8) -> String {
+    format!("{}/input{:02}.txt", dir.trim_end_matches('/'), day)
+}
+
+// Inputs of data directory at build time
+#[cfg(feature = "embedded-inputs")]
+fn embedded(day: u8) -> Option<&'static str> {
+    match day {
+        1 => Some(include_str!("../data/input01.txt")),
+        2 => Some(include_str!("../data/input02.txt")),
+        3 => Some(include_str!("../data/input03.txt")),
+        4 => Some(include_str!("../data/input04.txt")),
+        6 => Some(include_str!("../data/input06.txt")),
+        _ => None,
+    }
+}
+
+// Strips BOM, converts CRLF line endings, removes trailing whitespace of lines and
+// blank lines around content. Input is borrowed as is when nothing has to be changed.
+pub fn normalize(input: &str) -> Cow<'_, str> {
+    let mut input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    // Indentation of the first line with content is kept
+    while let Some((line, rest)) = input.split_once('\n') {
+        if !line.trim_end().is_empty() {
+            break;
+        }
+        input = rest;
+    }
+    let input = input.trim_end();
+
+    if !input.contains('\r') && input.lines().all(|l| l.len() == l.trim_end().len()) {
+        return Cow::Borrowed(input);
+    }
+
+    let lines: Vec<&str> = input.lines().map(str::trim_end).collect();
+    Cow::Owned(lines.join("\n"))
+}
+
+impl From<String> for Input {
+    fn from(s: String) -> Self {
+        Input::Owned(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read() {
+        let input = Input::read("data/input04.txt").unwrap();
+        assert_eq!(input.lines().count(), 1);
+        assert!(input.as_str().contains('-'));
+        assert!(Input::read("data/missing.txt").is_err());
+    }
+
+    // Reads content written to temporary file
+    fn read_temp(name: &str, content: &[u8]) -> Result<Input> {
+        let path = env::temp_dir().join(format!("aoc-{}-{}", std::process::id(), name));
+        fs::write(&path, content).unwrap();
+        let input = Input::read(path.to_str().unwrap());
+        fs::remove_file(&path).unwrap();
+        input
+    }
+
+    #[test]
+    fn test_read_gzip() {
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let original = fs::read_to_string("data/input03.txt").unwrap();
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(original.as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let input = read_temp("input03.txt.gz", &compressed).unwrap();
+        assert_eq!(input.as_str(), original);
+        assert!(read_temp("truncated.gz", &compressed[..compressed.len() / 2]).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "zstd")]
+    fn test_read_zstd() {
+        use ruzstd::encoding::{compress_to_vec, CompressionLevel};
+
+        let original = fs::read_to_string("data/input03.txt").unwrap();
+        let compressed = compress_to_vec(original.as_bytes(), CompressionLevel::Fastest);
+
+        let input = read_temp("input03.txt.zst", &compressed).unwrap();
+        assert_eq!(input.as_str(), original);
+    }
+
+    #[test]
+    #[cfg(feature = "embedded-inputs")]
+    fn test_read_embedded() {
+        let input = Input::read_day(4, "data/missing.txt").unwrap();
+        assert!(matches!(input, Input::Embedded(_)));
+        assert_eq!(
+            input.as_str(),
+            fs::read_to_string("data/input04.txt").unwrap()
+        );
+        assert!(Input::read_day(25, "data/missing.txt").is_err());
+    }
+
+    #[test]
+    fn test_default_path() {
+        if env::var_os("AOC_INPUT_DIR").is_none() {
+            assert_eq!(default_path(3), "data/input03.txt");
+        }
+        assert!(default_path(12).ends_with("/input12.txt"));
+        assert_eq!(path_in("inputs/", 3), "inputs/input03.txt");
+    }
+
+    #[test]
+    fn test_normalize() {
+        let cases = [
+            ("R8,U5\nU7,R6", "R8,U5\nU7,R6"),
+            ("R8,U5\r\nU7,R6\r\n", "R8,U5\nU7,R6"),
+            ("\u{feff}12\n14\n\n\n", "12\n14"),
+            ("\n\n12 \t\n 14\r", "12\n 14"),
+            ("  \r\n\t\n 12\n14", " 12\n14"),
+            ("  \n \t", ""),
+            ("", ""),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(normalize(input), expected, "input {:?}", input);
+        }
+
+        assert!(matches!(normalize("12\n14\n"), Cow::Borrowed(_)));
+        assert!(matches!(normalize("  \r\n12\n"), Cow::Borrowed(_)));
+    }
+}
@@ -1,5 +1,111 @@
-mod computer;
+#[macro_use]
+mod macros;
+
+#[cfg(feature = "alloc-stats")]
+pub mod alloc_stats;
+pub mod annotated;
+pub mod answers;
+pub mod cancel;
+pub mod computer;
 pub mod day01;
 pub mod day02;
 pub mod day03;
 pub mod day04;
+pub mod day06;
+pub mod explain;
+pub mod export;
+pub mod fixture;
+pub mod geometry;
+pub mod graph;
+pub mod input;
+pub mod math;
+pub mod metadata;
+pub mod parse;
+pub mod progress;
+pub mod render;
+pub mod report;
+pub mod search;
+pub mod viz;
+
+use anyhow::{Context, Result};
+use explain::Explainer;
+use progress::Progress;
+use render::Renderable;
+
+// Common interface for puzzle solutions, every part returns the answer as string.
+// Solutions are shared between threads, so they must not keep mutable state.
+pub trait Solution: Send + Sync {
+    fn part1(&self, input: &str) -> Result<String>;
+    fn part2(&self, input: &str) -> Result<String>;
+
+    // Only parses input, returns short summary of what was read
+    fn validate(&self, input: &str) -> Result<String>;
+
+    // Long running solvers override these to report their progress
+    fn part1_with_progress(&self, input: &str, _progress: &dyn Progress) -> Result<String> {
+        self.part1(input)
+    }
+
+    fn part2_with_progress(&self, input: &str, _progress: &dyn Progress) -> Result<String> {
+        self.part2(input)
+    }
+
+    // Reports key intermediate results, solutions without explanation report nothing
+    fn explain(&self, _input: &str, _explainer: &mut dyn Explainer) -> Result<()> {
+        Ok(())
+    }
+
+    // Solutions with visualization return themselves here
+    fn renderable(&self) -> Option<&dyn Renderable> {
+        None
+    }
+}
+
+// Configures global thread pool used by parallel solvers.
+// Zero picks number of threads automatically, 1 makes every solver sequential.
+pub fn set_threads(threads: usize) -> Result<()> {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build_global()
+        .context("Failed to configure thread pool")
+}
+
+// Every implemented solution with its day, new days are registered here
+pub static SOLUTIONS: &[(u8, &dyn Solution)] = &[
+    (1, &day01::Day01),
+    (2, &day02::Day02),
+    (3, &day03::Day03),
+    (4, &day04::Day04),
+    (6, &day06::Day06),
+];
+
+// Returns solution for the day if it's implemented
+pub fn solution(day: u8) -> Option<&'static dyn Solution> {
+    SOLUTIONS
+        .iter()
+        .find(|&&(d, _)| d == day)
+        .map(|&(_, solution)| solution)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_solutions_shared_between_threads() {
+        let answers: Vec<String> = thread::scope(|scope| {
+            let handles: Vec<_> = metadata::PROBLEMS
+                .iter()
+                .filter_map(|problem| problem.examples.first().map(|e| (problem.day, e)))
+                .map(|(day, example)| {
+                    let solution = solution(day).unwrap();
+                    scope.spawn(move || solution.part1(example.input).unwrap())
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        assert_eq!(answers, ["2", "6", "1", "42"]);
+    }
+}
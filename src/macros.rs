@@ -0,0 +1,22 @@
+// Expands to a module with a test per listed part, checking the answer
+// returned by the Solution API for the example input:
+//
+// aoc_example!(example1, Day03, "R8,U5,L5,D3\nU7,R6,D4,L4", part1 = "6", part2 = "30");
+#[macro_export]
+macro_rules! aoc_example {
+    ($name:ident, $solution:expr, $input:expr, $($part:ident = $expected:expr),+ $(,)?) => {
+        mod $name {
+            #[allow(unused_imports)]
+            use super::*;
+
+            $(
+                #[test]
+                fn $part() {
+                    let answer = $crate::Solution::$part(&$solution, $input)
+                        .expect("example should be solved");
+                    assert_eq!(answer, $expected);
+                }
+            )+
+        }
+    };
+}
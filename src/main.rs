@@ -1,51 +1,1175 @@
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Context, Result};
+use aoc_2019::cancel::{CancellationToken, Cancelled, Deadline};
+use aoc_2019::computer::{self, IntcodeComputer, Memory, StepResult};
+use aoc_2019::progress::{Progress, ProgressBar};
 use aoc_2019::*;
+use clap::{Args, Parser, Subcommand};
+use std::collections::BTreeSet;
 use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::num::ParseIntError;
 use std::process::exit;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+mod repl;
+
+#[cfg(feature = "alloc-stats")]
+#[global_allocator]
+static ALLOCATOR: aoc_2019::alloc_stats::CountingAllocator =
+    aoc_2019::alloc_stats::CountingAllocator;
+
+const AFTER_HELP: &str = "\
+Without command a day is solved like with `run`:
+  aoc-2019 [day] [input]...  patterns like 'inputs/*.txt' are expanded, '-' reads stdin
+  aoc-2019 4 <start> <end>
+
+Environment:
+  AOC_DAY        day to solve when it's not given
+  AOC_INPUT_DIR  directory with inputNN.txt files used when input is not given";
+
+#[derive(Parser)]
+#[command(version, about, after_help = AFTER_HELP)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[arg(value_parser = parse_day, help = "Day to solve, AOC_DAY by default")]
+    day: Option<u8>,
+    #[arg(help = "Input files, more than one are solved as a batch")]
+    inputs: Vec<String>,
+
+    #[command(flatten)]
+    options: SolveOptions,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "N",
+        help = "Size of thread pool used by parallel solvers"
+    )]
+    threads: Option<usize>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[command(about = "Solve one day or every implemented day")]
+    Run(RunArgs),
+    #[command(about = "Measure run time of solutions over repeated runs")]
+    Bench(BenchArgs),
+    #[command(about = "Show implemented days")]
+    List,
+    #[command(about = "Start interactive shell")]
+    Repl,
+    #[command(about = "Solve embedded examples of every day")]
+    VerifyExamples,
+    #[command(about = "Check answers of every implemented day against recorded ones")]
+    Verify {
+        #[arg(
+            long,
+            value_name = "PATH",
+            default_value = answers::DEFAULT_PATH,
+            help = "TOML file with accepted answers"
+        )]
+        answers: String,
+        #[arg(
+            long,
+            value_name = "DIR",
+            help = "Directory with inputNN.txt files, AOC_INPUT_DIR or data by default"
+        )]
+        input_dir: Option<String>,
+    },
+    #[command(subcommand, about = "Run standalone Intcode programs")]
+    Intcode(IntcodeCommand),
+    #[command(about = "Write input with structure of the given one, but new answers")]
+    Anonymize {
+        #[arg(value_parser = parse_day)]
+        day: u8,
+        input: String,
+        output: String,
+        #[arg(long, help = "Random seed, current time by default")]
+        seed: Option<u64>,
+    },
+    #[command(about = "Play visualization of the solution in terminal")]
+    Animate {
+        #[arg(value_parser = parse_day)]
+        day: u8,
+        input: Option<String>,
+        #[arg(long, default_value_t = 10.0, value_parser = parse_fps, help = "Frames per second")]
+        fps: f64,
+    },
+    #[command(about = "Write visualization of the solution to animated GIF or APNG file")]
+    Export {
+        #[arg(value_parser = parse_day)]
+        day: u8,
+        input: Option<String>,
+        #[arg(
+            long,
+            short,
+            value_name = "PATH",
+            help = "Animation file, .gif or .png extension selects the format"
+        )]
+        output: String,
+        #[arg(
+            long,
+            default_value_t = 4,
+            value_parser = clap::value_parser!(u16).range(1..=64),
+            help = "Pixels per cell side"
+        )]
+        scale: u16,
+        #[arg(
+            long,
+            value_name = "MS",
+            default_value_t = 100,
+            help = "Time every frame is shown in milliseconds"
+        )]
+        delay: u64,
+    },
+}
+
+#[derive(Subcommand)]
+enum IntcodeCommand {
+    #[command(about = "Run program until it halts, print its memory and output")]
+    Run {
+        program: String,
+        #[arg(
+            long,
+            value_name = "VALUES",
+            value_delimiter = ',',
+            allow_negative_numbers = true,
+            // Lists like -5,7 aren't a single number, but are values too
+            allow_hyphen_values = true,
+            help = "Comma separated values read by input instructions"
+        )]
+        input: Vec<i64>,
+        #[arg(
+            long,
+            value_name = "TEXT",
+            num_args = 0..=1,
+            default_missing_value = "",
+            help = "Feed text as character codes after --input, print ASCII output as text"
+        )]
+        ascii: Option<String>,
+        #[arg(
+            long,
+            value_name = "S",
+            value_parser = parse_timeout,
+            help = "Stop program running longer than S seconds"
+        )]
+        timeout: Option<Duration>,
+        #[command(flatten)]
+        debug: DebugOptions,
+    },
+    #[command(about = "Print program in annotated format, one instruction per line")]
+    Annotate { program: String },
+    #[command(about = "Print program as instructions with their addresses")]
+    Disassemble { program: String },
+}
+
+#[derive(Args)]
+struct DebugOptions {
+    #[arg(long, help = "Print every executed instruction to stderr")]
+    trace: bool,
+    #[arg(
+        long = "break",
+        value_name = "ADDRESS",
+        help = "Pause before executing instruction at address, can be repeated"
+    )]
+    breakpoints: Vec<usize>,
+}
+
+#[derive(Args)]
+struct RunArgs {
+    #[arg(long, short, value_parser = parse_day, help = "Day to solve, AOC_DAY by default")]
+    day: Option<u8>,
+    #[arg(long, conflicts_with_all = ["day", "inputs"], help = "Solve every implemented day")]
+    all: bool,
+    #[arg(
+        long = "input",
+        short,
+        value_name = "PATH",
+        help = "Input file, can be repeated to solve a batch, '-' reads stdin"
+    )]
+    inputs: Vec<String>,
+    #[arg(
+        long,
+        value_name = "DIR",
+        conflicts_with = "inputs",
+        help = "Directory with inputNN.txt files, AOC_INPUT_DIR or data by default"
+    )]
+    input_dir: Option<String>,
+
+    #[command(flatten)]
+    options: SolveOptions,
+}
+
+#[derive(Args)]
+struct SolveOptions {
+    #[arg(long, value_parser = clap::value_parser!(u8).range(1..=2), help = "Solve only this part")]
+    part: Option<u8>,
+    #[arg(long, help = "Only parse input and report problems found in it")]
+    dry_run: bool,
+    #[arg(
+        long,
+        value_name = "S",
+        value_parser = parse_timeout,
+        help = "Stop solvers running longer than S seconds"
+    )]
+    timeout: Option<Duration>,
+    #[arg(long, help = "Copy the last answer to the clipboard")]
+    copy: bool,
+    #[arg(long, help = "Show intermediate results before the answers")]
+    explain: bool,
+    #[arg(
+        long,
+        value_name = "A-B",
+        help = "Password range of day 4 given instead of input file"
+    )]
+    range: Option<String>,
+}
+
+impl SolveOptions {
+    fn parts(&self) -> Vec<u8> {
+        parts(self.part)
+    }
+}
+
+#[derive(Args)]
+struct BenchArgs {
+    #[arg(long, short, value_parser = parse_day, help = "Day to measure, AOC_DAY by default")]
+    day: Option<u8>,
+    #[arg(long, conflicts_with_all = ["day", "input"], help = "Measure every implemented day")]
+    all: bool,
+    #[arg(long, short, value_name = "PATH", help = "Input file, '-' reads stdin")]
+    input: Option<String>,
+    #[arg(
+        long,
+        value_name = "DIR",
+        conflicts_with = "input",
+        help = "Directory with inputNN.txt files, AOC_INPUT_DIR or data by default"
+    )]
+    input_dir: Option<String>,
+    #[arg(long, value_parser = clap::value_parser!(u8).range(1..=2), help = "Measure only this part")]
+    part: Option<u8>,
+    #[arg(
+        long,
+        short = 'n',
+        default_value_t = 10,
+        value_parser = clap::value_parser!(u32).range(1..),
+        help = "Number of runs of every part"
+    )]
+    runs: u32,
+}
+
+// Selected part or both of them
+fn parts(part: Option<u8>) -> Vec<u8> {
+    match part {
+        Some(part) => vec![part],
+        None => vec![1, 2],
+    }
+}
+
+fn parse_day(day: &str) -> Result<u8> {
+    metadata::parse_day(day)
+}
+
+fn parse_timeout(timeout: &str) -> Result<Duration> {
+    let secs: f64 = timeout.parse().context("Error parsing timeout")?;
+    Duration::try_from_secs_f64(secs).context("Invalid timeout")
+}
+
+fn parse_fps(fps: &str) -> Result<f64> {
+    let fps: f64 = fps.parse().context("Error parsing frame rate")?;
+    if !fps.is_finite() || fps <= 0.0 {
+        bail!("Frame rate must be positive");
+    }
+    Ok(fps)
+}
+
+// Command line accepted by parser whose arguments can't be used together,
+// reported like parser errors with exit status 1
+#[derive(Debug)]
+struct UsageError(anyhow::Error);
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:#}", self.0)
+    }
+}
+
+impl std::error::Error for UsageError {}
 
 struct Config {
     day: u8,
     filename: String,
+    // Every input when more than one is solved
+    batch: Vec<String>,
+    // Input given on command line instead of file
+    input: Option<String>,
 }
 
 impl Config {
-    fn new(args: &[String]) -> Result<Config, &'static str> {
-        if args.len() < 3 {
-            return Err("Invalid number of arguments");
+    // Input of the day from the input directory is used when none is given
+    fn new(day: u8, inputs: &[String], input_dir: Option<&str>) -> Result<Config> {
+        // Day 4 range can be given as two bounds
+        if let [start, end] = inputs {
+            if day == 4 && is_number(start) && is_number(end) {
+                return Ok(Config {
+                    day,
+                    filename: String::new(),
+                    batch: Vec::new(),
+                    input: Some(format!("{}-{}", start, end)),
+                });
+            }
+        }
+
+        let mut paths = Vec::new();
+        for arg in inputs {
+            paths.extend(expand_pattern(arg)?);
         }
-        let day = match args[1].parse::<u8>() {
-            Ok(val) => val,
-            Err(_err) => return Err("Error parsing day number"),
+        let filename = match (paths.first(), input_dir) {
+            (Some(path), _) => path.clone(),
+            (None, Some(dir)) => input::path_in(dir, day),
+            (None, None) => input::default_path(day),
         };
-        let filename = args[2].clone();
+        let batch = if paths.len() > 1 { paths } else { Vec::new() };
 
-        Ok(Config { day, filename })
+        Ok(Config {
+            day,
+            filename,
+            batch,
+            input: None,
+        })
+    }
+
+    // Path shown in diagnostics, None when input is given on command line
+    fn source(&self) -> Option<&str> {
+        match (&self.input, self.filename.as_str()) {
+            (Some(_), _) => None,
+            (None, input::STDIN) => Some("<stdin>"),
+            (None, filename) => Some(filename),
+        }
+    }
+
+    fn load(&self) -> Result<input::Input> {
+        match &self.input {
+            Some(input) => Ok(input::Input::from(input.clone())),
+            None => input::Input::read_day(self.day, &self.filename),
+        }
     }
 }
 
+// Returns day given on command line or by AOC_DAY
+fn day_or_env(day: Option<u8>) -> Result<u8> {
+    match day {
+        Some(day) => Ok(day),
+        None => match env::var("AOC_DAY") {
+            Ok(day) => metadata::parse_day(&day).context("Invalid AOC_DAY"),
+            Err(_) => bail!("Day is not specified and AOC_DAY is not set"),
+        },
+    }
+}
+
+fn is_number(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_digit())
+}
+
+// Paths with wildcards are replaced with matching files, others are kept as is
+fn expand_pattern(path: &str) -> Result<Vec<String>> {
+    if !path.contains(['*', '?', '[']) {
+        return Ok(vec![path.to_string()]);
+    }
+
+    let paths = glob::glob(path)
+        .with_context(|| format!("Invalid pattern {}", path))?
+        .map(|entry| entry.map(|p| p.display().to_string()))
+        .collect::<Result<Vec<_>, _>>()?;
+    if paths.is_empty() {
+        bail!("No files match {}", path);
+    }
+    Ok(paths)
+}
+
+// Returns every implemented day or the selected one
+fn days(day: Option<u8>, all: bool) -> Result<Vec<u8>> {
+    if all {
+        Ok(metadata::PROBLEMS
+            .iter()
+            .map(|problem| problem.day)
+            .collect())
+    } else {
+        Ok(vec![day_or_env(day)?])
+    }
+}
+
+// Builds configuration of every day to solve
+fn configs(args: &RunArgs) -> Result<Vec<Config>> {
+    let mut configs = Vec::new();
+    for day in days(args.day, args.all)? {
+        let mut config = Config::new(day, &args.inputs, args.input_dir.as_deref())?;
+        if let Some(range) = &args.options.range {
+            if day != 4 {
+                bail!("--range is only accepted by day 4");
+            }
+            config.input = Some(range.clone());
+            config.batch.clear();
+        }
+        configs.push(config);
+    }
+    Ok(configs)
+}
+
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    let config = Config::new(&args).unwrap_or_else(|err| {
-        println!("Coulnd't parse arguments: {}", err);
+    let cli = Cli::try_parse().unwrap_or_else(|err| {
+        // Help and version aren't errors
+        if !err.use_stderr() {
+            err.exit();
+        }
+        let _ = err.print();
         exit(1);
     });
 
-    println!("Day: {}\nFilename: {}", config.day, config.filename);
+    if let Some(threads) = cli.threads {
+        if let Err(err) = set_threads(threads) {
+            report_error(&err);
+            exit(1);
+        }
+    }
+
+    let command = cli.command.unwrap_or(Command::Run(RunArgs {
+        day: cli.day,
+        all: false,
+        inputs: cli.inputs,
+        input_dir: None,
+        options: cli.options,
+    }));
+    match command {
+        Command::Run(args) => solve(args),
+        Command::Bench(args) => bench(args),
+        Command::List => list(),
+        Command::Repl => exit_on_error(repl::run()),
+        Command::VerifyExamples => {
+            if !verify_examples() {
+                exit(2);
+            }
+        }
+        Command::Verify { answers, input_dir } => {
+            // Input hints don't apply to the answers file
+            let passed = verify(&answers, input_dir.as_deref()).unwrap_or_else(|err| {
+                eprintln!("error: {:#}", err);
+                exit(2);
+            });
+            if !passed {
+                exit(2);
+            }
+        }
+        Command::Intcode(IntcodeCommand::Run {
+            program,
+            input,
+            ascii,
+            timeout,
+            debug,
+        }) => exit_on_error_in(
+            intcode_run(&program, &input, ascii.as_deref(), timeout, &debug),
+            Some(&program),
+        ),
+        Command::Intcode(IntcodeCommand::Annotate { program }) => {
+            exit_on_error_in(intcode_annotate(&program), Some(&program))
+        }
+        Command::Intcode(IntcodeCommand::Disassemble { program }) => {
+            exit_on_error_in(intcode_disassemble(&program), Some(&program))
+        }
+        Command::Anonymize {
+            day,
+            input,
+            output,
+            seed,
+        } => exit_on_error(anonymize(day, &input, &output, seed)),
+        Command::Animate { day, input, fps } => {
+            let inputs: Vec<String> = input.into_iter().collect();
+            exit_on_error(Config::new(day, &inputs, None).and_then(|config| animate(config, fps)))
+        }
+        Command::Export {
+            day,
+            input,
+            output,
+            scale,
+            delay,
+        } => {
+            let inputs: Vec<String> = input.into_iter().collect();
+            let options = export::Options {
+                scale: scale as usize,
+                delay: Duration::from_millis(delay),
+            };
+            exit_on_error(
+                Config::new(day, &inputs, None)
+                    .and_then(|config| export(config, &output, &options)),
+            )
+        }
+    }
+}
+
+// Reports error of finished command and exits with failure status
+fn exit_on_error(result: Result<()>) {
+    exit_on_error_in(result, None)
+}
+
+fn exit_usage(err: &UsageError) -> ! {
+    eprintln!("error: {}\n\nFor more information, try '--help'.", err);
+    exit(1);
+}
+
+// Like exit_on_error, input parse errors point at the file they were found in
+fn exit_on_error_in(result: Result<()>, source: Option<&str>) {
+    let Err(err) = result else {
+        return;
+    };
+    if err.is::<Cancelled>() {
+        eprintln!("Interrupted");
+        exit(130);
+    }
+    if let Some(err) = err.downcast_ref::<UsageError>() {
+        exit_usage(err);
+    }
+    report_error_in(&err, source);
+    exit(2);
+}
+
+// Solves selected days one after another. Failure of a day is reported and
+// the remaining days are still solved, exit status tells whether any failed.
+fn solve(args: RunArgs) {
+    let configs = configs(&args).unwrap_or_else(|err| exit_usage(&UsageError(err)));
+    let options = &args.options;
+
+    // Answers found before Ctrl-C are still printed
+    let token = CancellationToken::new();
+    exit_on_error(token.cancel_on_ctrl_c());
+
+    let mut failed = false;
+    let mut last = None;
+    for (i, config) in configs.into_iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        let source = config.source().map(str::to_string);
+        let result = if !config.batch.is_empty() {
+            batch(&config, options, &token).map(|passed| failed |= !passed)
+        } else {
+            match &config.input {
+                Some(input) => println!("Day: {}\nInput: {}", config.day, input),
+                None => println!("Day: {}\nFilename: {}", config.day, config.filename),
+            }
+            if options.dry_run {
+                validate(config)
+            } else {
+                run(config, options, &token).map(|answer| last = Some(answer))
+            }
+        };
 
-    if let Err(err) = run(config) {
-        println!("{:?}", err);
+        if let Err(err) = result {
+            if err.is::<Cancelled>() {
+                eprintln!("Interrupted");
+                exit(130);
+            }
+            report_error_in(&err, source.as_deref());
+            failed = true;
+        }
+    }
+
+    if let (true, Some(answer)) = (options.copy, last) {
+        exit_on_error(copy(&answer));
+    }
+    if failed {
         exit(2);
     }
 }
 
-fn run(config: Config) -> Result<()> {
-    let result = match config.day {
-        1 => day01::solve(&config.filename),
-        2 => day02::solve(&config.filename),
-        3 => day03::solve(&config.filename),
-        4 => day04::solve(&config.filename),
-        _ => bail!("Invalid day number"),
+// Returns answer of the last solved part
+fn run(config: Config, options: &SolveOptions, token: &CancellationToken) -> Result<String> {
+    let day = config.day;
+    let solution = solution(day).ok_or(anyhow!("Day {} is not implemented", day))?;
+    let input = config
+        .load()
+        .with_context(|| format!("Failed to solve day {}", day))?;
+    let input = input.normalized();
+
+    if options.explain {
+        explain(solution, &input).with_context(|| format!("Failed to explain day {}", day))?;
+    }
+
+    let bar = ProgressBar::new().cancellable(token.clone());
+    let mut last = String::new();
+    for part in options.parts() {
+        // Every part gets its own time limit
+        let deadline = options
+            .timeout
+            .map(|timeout| Deadline::after(timeout, &bar));
+        let progress: &dyn Progress = match &deadline {
+            Some(deadline) => deadline,
+            None => &bar,
+        };
+
+        let answer = solve_part(solution, part, &input, progress);
+        bar.finish();
+        last = answer.with_context(|| format!("Failed to solve day {} part {}", day, part))?;
+        println!("answer {}: {}", part, last);
+    }
+    Ok(last)
+}
+
+fn solve_part(
+    solution: &dyn Solution,
+    part: u8,
+    input: &str,
+    progress: &dyn Progress,
+) -> Result<String> {
+    if part == 1 {
+        solution.part1_with_progress(input, progress)
+    } else {
+        solution.part2_with_progress(input, progress)
+    }
+}
+
+#[cfg(feature = "clipboard")]
+fn copy(answer: &str) -> Result<()> {
+    let mut clipboard = arboard::Clipboard::new().context("Failed to access clipboard")?;
+    clipboard
+        .set_text(answer)
+        .context("Failed to copy answer to clipboard")?;
+    println!("Copied {} to clipboard", answer);
+    Ok(())
+}
+
+#[cfg(not(feature = "clipboard"))]
+fn copy(_answer: &str) -> Result<()> {
+    bail!("Clipboard support is disabled, rebuild with `clipboard` feature")
+}
+
+fn explain(solution: &dyn Solution, input: &str) -> Result<()> {
+    let mut explanation = explain::Explanation::default();
+    solution.explain(input, &mut explanation)?;
+    if explanation.is_empty() {
+        println!("No explanation available");
+    } else {
+        print!("{}", explanation);
+    }
+    Ok(())
+}
+
+// Solves every input of the batch and prints table of answers, returns false
+// if any of them failed. Answers found before Ctrl-C are printed as well.
+fn batch(config: &Config, options: &SolveOptions, token: &CancellationToken) -> Result<bool> {
+    let day = config.day;
+    let solution = solution(day).ok_or(anyhow!("Day {} is not implemented", day))?;
+    let parts = options.parts();
+
+    let mut header = vec!["Input".to_string()];
+    header.extend(parts.iter().map(|part| format!("Part {}", part)));
+    let mut table = report::Table::new(header);
+    let bar = ProgressBar::new().cancellable(token.clone());
+    let mut cancelled = false;
+
+    for path in &config.batch {
+        let mut row = vec![path.clone()];
+        match input::Input::read(path) {
+            Ok(input) => {
+                let input = input.normalized();
+                for &part in &parts {
+                    let deadline = options
+                        .timeout
+                        .map(|timeout| Deadline::after(timeout, &bar));
+                    let progress: &dyn Progress = match &deadline {
+                        Some(deadline) => deadline,
+                        None => &bar,
+                    };
+
+                    let answer = solve_part(solution, part, &input, progress);
+                    bar.finish();
+                    match answer {
+                        Ok(answer) => row.push(answer),
+                        Err(err) if err.is::<Cancelled>() => {
+                            cancelled = true;
+                            break;
+                        }
+                        Err(err) => row.push(format!("error: {}", err)),
+                    }
+                }
+            }
+            Err(err) => {
+                let err = format!("error: {}", err);
+                row.extend(parts.iter().map(|_| err.clone()));
+            }
+        }
+        table.push(row);
+        if cancelled {
+            break;
+        }
+    }
+
+    println!("Day: {}", day);
+    print!("{}", table);
+    if cancelled {
+        return Err(Cancelled.into());
+    }
+
+    Ok(!table
+        .rows()
+        .iter()
+        .any(|row| row[1..].iter().any(|cell| cell.starts_with("error"))))
+}
+
+// Solves selected parts several times and prints table of their run times.
+// Failure of a day is reported and the remaining days are still measured,
+// Ctrl-C stops measurement and prints rows measured so far.
+fn bench(args: BenchArgs) {
+    let days = days(args.day, args.all).unwrap_or_else(|err| exit_usage(&UsageError(err)));
+
+    let token = CancellationToken::new();
+    exit_on_error(token.cancel_on_ctrl_c());
+
+    let mut header = vec!["Day", "Part", "Runs", "Min", "Mean", "Max"];
+    if cfg!(feature = "alloc-stats") {
+        header.push("Peak heap");
+    }
+    let mut table = report::Table::new(header);
+    let mut failed = false;
+    for day in days {
+        match bench_day(day, &args, &token, &mut table) {
+            Ok(()) => {}
+            Err(err) if err.is::<Cancelled>() => {
+                print!("{}", table);
+                eprintln!("Interrupted");
+                exit(130);
+            }
+            Err(err) => {
+                report_error(&err);
+                failed = true;
+            }
+        }
+    }
+
+    print!("{}", table);
+    if failed {
+        exit(2);
+    }
+}
+
+// Input is read once before measurement, so only the solver is timed.
+// Rows are added as parts are measured, so interrupted run keeps them.
+fn bench_day(
+    day: u8,
+    args: &BenchArgs,
+    token: &CancellationToken,
+    table: &mut report::Table,
+) -> Result<()> {
+    let solution = solution(day).ok_or(anyhow!("Day {} is not implemented", day))?;
+    let inputs: Vec<String> = args.input.iter().cloned().collect();
+    let config = Config::new(day, &inputs, args.input_dir.as_deref())?;
+    let input = config
+        .load()
+        .with_context(|| format!("Failed to measure day {}", day))?;
+    let input = input.normalized();
+
+    for part in parts(args.part) {
+        #[cfg(feature = "alloc-stats")]
+        let baseline = alloc_stats::start_heap_measurement();
+        let measured = report::measure(args.runs as usize, || {
+            token.check()?;
+            solve_part(solution, part, &input, token)
+        });
+        let (_, stats) = match measured {
+            Err(err) if err.is::<Cancelled>() => return Err(err),
+            measured => {
+                measured.with_context(|| format!("Failed to solve day {} part {}", day, part))?
+            }
+        };
+
+        #[cfg(feature = "alloc-stats")]
+        let heap = Some(report::format_bytes(alloc_stats::peak_heap(baseline)));
+        #[cfg(not(feature = "alloc-stats"))]
+        let heap = None;
+        let row = [
+            format!("{:02}", day),
+            part.to_string(),
+            stats.runs.to_string(),
+            report::format_duration(stats.min),
+            report::format_duration(stats.mean),
+            report::format_duration(stats.max),
+        ];
+        table.push(row.into_iter().chain(heap));
+    }
+    Ok(())
+}
+
+// Parses input without solving the puzzle
+fn validate(config: Config) -> Result<()> {
+    let day = config.day;
+    let solution = solution(day).ok_or(anyhow!("Day {} is not implemented", day))?;
+    let input = config.load()?;
+    let summary = solution
+        .validate(&input.normalized())
+        .with_context(|| format!("Invalid input for day {}", day))?;
+
+    println!("Input is valid: {}", summary);
+    Ok(())
+}
+
+// Prints error with all its causes and hint how to fix it when one is known
+fn report_error(err: &anyhow::Error) {
+    report_error_in(err, None)
+}
+
+// Input parse errors also point at the line of the file they were found on
+fn report_error_in(err: &anyhow::Error, source: Option<&str>) {
+    eprintln!("error: {}", err);
+    for cause in err.chain().skip(1) {
+        eprintln!("  caused by: {}", cause);
+    }
+    let location = err
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<parse::ParseError>());
+    if let (Some(source), Some(location)) = (source, location) {
+        eprintln!("  --> {}:{}:{}", source, location.line, location.column);
+    }
+
+    if let Some(hint) = hint(err) {
+        eprintln!("hint: {}", hint);
+    }
+}
+
+fn hint(err: &anyhow::Error) -> Option<&'static str> {
+    if is_not_found(err) {
+        return Some(
+            "input file not found, check the path or download the input from adventofcode.com",
+        );
+    }
+    for cause in err.chain() {
+        if cause.is::<ParseIntError>() || cause.is::<parse::ParseError>() {
+            return Some(
+                "input doesn't look like puzzle input of this day, check the day number and file",
+            );
+        }
+    }
+
+    None
+}
+
+fn is_not_found(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        cause
+            .downcast_ref::<io::Error>()
+            .is_some_and(|err| err.kind() == io::ErrorKind::NotFound)
+    })
+}
+
+fn list() {
+    for problem in metadata::PROBLEMS {
+        let intcode = if problem.intcode_parts.is_empty() {
+            ""
+        } else {
+            " [intcode]"
+        };
+        println!(
+            "Day {:02}: {}{} ({} examples)",
+            problem.day,
+            problem.title,
+            intcode,
+            problem.examples.len()
+        );
+    }
+}
+
+// Solves embedded examples of every day, returns false if any of them failed
+fn verify_examples() -> bool {
+    let mut failures = Vec::new();
+    println!("Day  Example  Part 1  Part 2");
+    for problem in metadata::PROBLEMS {
+        for (i, example) in problem.examples.iter().enumerate() {
+            let outcomes = metadata::check_example(problem.day, example);
+            let status: Vec<&str> = outcomes
+                .iter()
+                .map(|outcome| match outcome {
+                    metadata::Outcome::Pass => "pass",
+                    metadata::Outcome::Skip => "-",
+                    metadata::Outcome::Fail { .. } | metadata::Outcome::Error(_) => "FAIL",
+                })
+                .collect();
+            println!(
+                "{:02}   {:<7}  {:<6}  {}",
+                problem.day,
+                i + 1,
+                status[0],
+                status[1]
+            );
+
+            for (part, outcome) in outcomes.iter().enumerate() {
+                let reason = match outcome {
+                    metadata::Outcome::Fail { expected, actual } => {
+                        format!("expected {}, got {}", expected, actual)
+                    }
+                    metadata::Outcome::Error(err) => err.clone(),
+                    _ => continue,
+                };
+                failures.push(format!(
+                    "day {:02} example {} part {}: {}",
+                    problem.day,
+                    i + 1,
+                    part + 1,
+                    reason
+                ));
+            }
+        }
+    }
+
+    for failure in &failures {
+        println!("{}", failure);
+    }
+    failures.is_empty()
+}
+
+// Solves every implemented day with its input and compares answers with recorded ones.
+// Parts without input, recorded answer or solution are missing, only wrong answers
+// and errors make verification fail.
+fn verify(path: &str, input_dir: Option<&str>) -> Result<bool> {
+    let answers = answers::Answers::read(path)?;
+    let mut table = report::Table::new(["Day", "Part 1", "Part 2"]);
+    let mut notes = Vec::new();
+    let (mut passed, mut failed, mut missing) = (0, 0, 0);
+
+    let days: BTreeSet<u8> = metadata::PROBLEMS
+        .iter()
+        .map(|problem| problem.day)
+        .chain(answers.days())
+        .collect();
+    for day in days {
+        let config = Config::new(day, &[], input_dir)?;
+        let outcomes = if solution(day).is_none() {
+            notes.push(format!("day {:02}: not implemented", day));
+            [metadata::Outcome::Skip, metadata::Outcome::Skip]
+        } else {
+            match config.load() {
+                Ok(input) => answers::check(day, &input.normalized(), &answers),
+                Err(err) if is_not_found(&err) => {
+                    notes.push(format!("day {:02}: no input {}", day, config.filename));
+                    [metadata::Outcome::Skip, metadata::Outcome::Skip]
+                }
+                Err(err) => {
+                    let err = format!("{:#}", err);
+                    [
+                        metadata::Outcome::Error(err.clone()),
+                        metadata::Outcome::Error(err),
+                    ]
+                }
+            }
+        };
+
+        let mut row = vec![format!("{:02}", day)];
+        for (part, outcome) in outcomes.iter().enumerate() {
+            let (status, note) = match outcome {
+                metadata::Outcome::Pass => ("pass", None),
+                metadata::Outcome::Skip => ("missing", None),
+                metadata::Outcome::Fail { expected, actual } => (
+                    "FAIL",
+                    Some(format!("expected {}, got {}", expected, actual)),
+                ),
+                metadata::Outcome::Error(err) => ("FAIL", Some(err.clone())),
+            };
+            match status {
+                "pass" => passed += 1,
+                "missing" => missing += 1,
+                _ => failed += 1,
+            }
+            if let Some(note) = note {
+                notes.push(format!("day {:02} part {}: {}", day, part + 1, note));
+            }
+            row.push(status.to_string());
+        }
+        table.push(row);
+    }
+
+    print!("{}", table);
+    for note in &notes {
+        println!("{}", note);
+    }
+    println!("{} passed, {} failed, {} missing", passed, failed, missing);
+    Ok(failed == 0)
+}
+
+// Runs standalone Intcode program until it halts, prints final memory and output.
+// Traced instructions and debugger prompts go to stderr. With ascii text, it's fed
+// as one line of character codes and output values 0-127 are printed as characters.
+fn intcode_run(
+    path: &str,
+    input: &[i64],
+    ascii: Option<&str>,
+    timeout: Option<Duration>,
+    debug: &DebugOptions,
+) -> Result<()> {
+    let program = input::Input::read(path)?;
+    let mut computer = IntcodeComputer::new(&program.normalized())?;
+    for &value in input {
+        computer.push_input(value);
+    }
+    if let Some(text) = ascii.filter(|text| !text.is_empty()) {
+        for c in text.chars().chain((!text.ends_with('\n')).then_some('\n')) {
+            if !c.is_ascii() {
+                bail!("ASCII input can't contain {:?}", c);
+            }
+            computer.push_input(c as i64);
+        }
+    }
+
+    // Programs with jumps may never halt, they are stopped by Ctrl-C or timeout
+    let token = CancellationToken::new();
+    token.cancel_on_ctrl_c()?;
+    let bar = ProgressBar::new().cancellable(token);
+    let deadline = timeout.map(|timeout| Deadline::after(timeout, &bar));
+    let progress: &dyn Progress = match &deadline {
+        Some(deadline) => deadline,
+        None => &bar,
     };
 
-    result
+    let mut output = Vec::new();
+    let mut stepping = false;
+    let mut steps = 0;
+    while !computer.is_halted() {
+        steps += 1;
+        if steps % computer::CHECK_INTERVAL == 0 {
+            progress.check()?;
+        }
+        if stepping || debug.breakpoints.contains(&computer.ip()) {
+            eprint!("{}", computer);
+            stepping = match debug_prompt()? {
+                DebugAction::Step => true,
+                DebugAction::Continue => false,
+                DebugAction::Quit => bail!("Stopped at address {}", computer.ip()),
+            };
+        }
+        if debug.trace {
+            eprintln!("{}", computer.trace());
+        }
+
+        // Faults tell the address themselves
+        match computer.step()? {
+            Some(StepResult::Output(value)) => output.push(value),
+            Some(StepResult::NeedsInput) => {
+                bail!("Program needs more input at address {}", computer.ip())
+            }
+            _ => {}
+        }
+    }
+
+    let ram = computer.ram();
+    let values = (0..ram.len())
+        .map(|address| ram.read(address).map(|v| v.to_string()))
+        .collect::<Result<Vec<_>>>()?;
+    println!("{}", values.join(","));
+    if output.is_empty() {
+        return Ok(());
+    }
+    if ascii.is_some() {
+        // Values outside ASCII, like final answers, are printed as numbers
+        let text: String = output
+            .iter()
+            .map(|&value| match u8::try_from(value) {
+                Ok(c) if c.is_ascii() => char::from(c).to_string(),
+                _ => value.to_string(),
+            })
+            .collect();
+        println!("Output:\n{}", text.strip_suffix('\n').unwrap_or(&text));
+    } else {
+        let values: Vec<String> = output.iter().map(i64::to_string).collect();
+        println!("Output: {}", values.join(","));
+    }
+    Ok(())
+}
+
+enum DebugAction {
+    Step,
+    Continue,
+    Quit,
+}
+
+// Asks what to do at breakpoint, execution continues when stdin is closed
+fn debug_prompt() -> Result<DebugAction> {
+    loop {
+        eprint!("[s]tep, [c]ontinue or [q]uit> ");
+        let mut line = String::new();
+        if io::stdin().read_line(&mut line)? == 0 {
+            eprintln!();
+            return Ok(DebugAction::Continue);
+        }
+        match line.trim() {
+            "" | "s" | "step" => return Ok(DebugAction::Step),
+            "c" | "continue" => return Ok(DebugAction::Continue),
+            "q" | "quit" => return Ok(DebugAction::Quit),
+            _ => {}
+        }
+    }
+}
+
+// Prints program in annotated format, one instruction per line
+fn intcode_annotate(path: &str) -> Result<()> {
+    let program = input::Input::read(path)?;
+    let values = annotated::parse(&program.normalized())?;
+    print!("{}", annotated::format(&values));
+    Ok(())
+}
+
+fn intcode_disassemble(path: &str) -> Result<()> {
+    let program = input::Input::read(path)?;
+    let computer = IntcodeComputer::new(&program.normalized())?;
+    print!("{}", computer.disassemble());
+    Ok(())
+}
+
+fn animate(config: Config, fps: f64) -> Result<()> {
+    let solution = solution(config.day).ok_or(anyhow!("Day {} is not implemented", config.day))?;
+    let renderable = solution
+        .renderable()
+        .ok_or(anyhow!("Day {} has no visualization", config.day))?;
+
+    let input = config.load()?;
+    let (width, height) = viz::viewport();
+    let frames = renderable.render(&input.normalized(), width, height)?;
+    viz::Player::new(fps).play(&frames)
+}
+
+// Size in cells exported frames are scaled down to
+const EXPORT_CELLS: (usize, usize) = (320, 240);
+
+fn export(config: Config, output: &str, options: &export::Options) -> Result<()> {
+    let solution = solution(config.day).ok_or(anyhow!("Day {} is not implemented", config.day))?;
+    let renderable = solution
+        .renderable()
+        .ok_or(anyhow!("Day {} has no visualization", config.day))?;
+
+    // Unknown format is reported before solving
+    export::Format::from_path(output)?;
+    let input = config.load()?;
+    let (width, height) = EXPORT_CELLS;
+    let frames = renderable.render(&input.normalized(), width, height)?;
+    export::write(output, &frames, options)?;
+
+    println!("Wrote {} frames to {}", frames.len(), output);
+    Ok(())
+}
+
+fn anonymize(day: u8, input: &str, output: &str, seed: Option<u64>) -> Result<()> {
+    let seed = seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default()
+    });
+
+    let input = input::Input::read(input)?;
+    let fixture = fixture::anonymize(day, &input.normalized(), seed)?;
+    fs::write(output, &fixture.input)?;
+
+    println!("Seed: {}", seed);
+    println!("answer 1: {}", fixture.part1);
+    println!("answer 2: {}", fixture.part2);
+    Ok(())
 }
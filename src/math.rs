@@ -0,0 +1,175 @@
+// Number theory helpers, intermediate results are computed in 128 bits
+// so none of them overflows for any 64-bit arguments
+
+pub fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+// Returns None when the result doesn't fit into 64 bits
+pub fn checked_lcm(a: u64, b: u64) -> Option<u64> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    let lcm = (a / gcd(a, b)) as u128 * b as u128;
+    u64::try_from(lcm).ok()
+}
+
+// Computes a * b mod m, result is always in 0..m. Modulus must be positive.
+pub fn mod_mul(a: i64, b: i64, m: i64) -> i64 {
+    assert!(m > 0, "modulus must be positive, got {}", m);
+    (a as i128 * b as i128).rem_euclid(m as i128) as i64
+}
+
+// Computes base^exp mod m by repeated squaring
+pub fn mod_pow(base: i64, mut exp: u64, m: i64) -> i64 {
+    let mut base = mod_mul(base, 1, m);
+    let mut result = 1 % m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mod_mul(result, base, m);
+        }
+        base = mod_mul(base, base, m);
+        exp >>= 1;
+    }
+    result
+}
+
+// Returns x such that a * x = 1 mod m, None when a and m aren't coprime
+pub fn mod_inv(a: i64, m: i64) -> Option<i64> {
+    assert!(m > 0, "modulus must be positive, got {}", m);
+    let (g, x, _) = extended_gcd(a as i128, m as i128);
+    if g != 1 {
+        return None;
+    }
+    Some(x.rem_euclid(m as i128) as i64)
+}
+
+// Combines x = a1 mod m1 and x = a2 mod m2 into x = a mod lcm(m1, m2).
+// Moduli don't have to be coprime. Returns None when congruences contradict
+// each other or the combined modulus doesn't fit into 64 bits.
+pub fn crt(a1: i64, m1: i64, a2: i64, m2: i64) -> Option<(i64, i64)> {
+    assert!(m1 > 0 && m2 > 0, "moduli must be positive");
+    let (a1, m1, a2, m2) = (a1 as i128, m1 as i128, a2 as i128, m2 as i128);
+
+    let (g, _, _) = extended_gcd(m1, m2);
+    let diff = a2 - a1;
+    if diff % g != 0 {
+        return None;
+    }
+
+    let lcm = m1 / g * m2;
+    if lcm > i64::MAX as i128 {
+        return None;
+    }
+
+    // Find k such that a1 + m1 * k = a2 mod m2
+    let m2g = m2 / g;
+    let (_, inv, _) = extended_gcd((m1 / g).rem_euclid(m2g), m2g);
+    let k = (diff / g).rem_euclid(m2g) * inv.rem_euclid(m2g) % m2g;
+    let a = (a1 + m1 * k).rem_euclid(lcm);
+
+    Some((a as i64, lcm as i64))
+}
+
+// Folds list of (residue, modulus) congruences with crt
+pub fn crt_all(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    congruences
+        .iter()
+        .try_fold((0, 1), |(a, m), &(a2, m2)| crt(a, m, a2, m2))
+}
+
+// Returns (g, x, y) such that a * x + b * y = g = gcd(a, b)
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gcd_lcm() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(0, 7), 7);
+        assert_eq!(gcd(u64::MAX, u64::MAX - 1), 1);
+
+        assert_eq!(checked_lcm(4, 6), Some(12));
+        assert_eq!(checked_lcm(0, 6), Some(0));
+        assert_eq!(checked_lcm(u64::MAX, 1), Some(u64::MAX));
+        assert_eq!(checked_lcm(u64::MAX, u64::MAX), Some(u64::MAX));
+        assert_eq!(checked_lcm(u64::MAX, 2), None);
+        assert_eq!(checked_lcm(1 << 32, (1 << 32) + 1), None);
+    }
+
+    #[test]
+    fn test_mod_mul() {
+        let m = i64::MAX;
+        assert_eq!(mod_mul(m - 1, m - 1, m), 1);
+        assert_eq!(mod_mul(i64::MIN, i64::MIN, m), 1);
+        assert_eq!(mod_mul(-3, 5, 7), 6);
+        assert_eq!(mod_mul(i64::MAX, i64::MIN, 1), 0);
+
+        // Day 22 sized deck
+        assert_eq!(
+            mod_mul(119315717514046, 101741582076661, 119315717514047),
+            17574135437386
+        );
+    }
+
+    #[test]
+    fn test_mod_pow_inv() {
+        let p = 119315717514047;
+        assert_eq!(mod_pow(2, 10, 1000), 24);
+        assert_eq!(mod_pow(5, 0, 1), 0);
+        assert_eq!(mod_pow(-2, 3, 7), 6);
+        // Fermat's little theorem
+        assert_eq!(mod_pow(123456789, p as u64 - 1, p), 1);
+        assert_eq!(mod_pow(i64::MAX - 1, u64::MAX, i64::MAX), i64::MAX - 1);
+
+        assert_eq!(mod_inv(3, 7), Some(5));
+        assert_eq!(mod_inv(-3, 7), Some(2));
+        assert_eq!(mod_inv(4, 8), None);
+        let inv = mod_inv(i64::MAX - 1, i64::MAX).unwrap();
+        assert_eq!(mod_mul(inv, i64::MAX - 1, i64::MAX), 1);
+        let inv = mod_inv(101741582076661, p).unwrap();
+        assert_eq!(mod_mul(inv, 101741582076661, p), 1);
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt(2, 3, 3, 5), Some((8, 15)));
+        assert_eq!(crt_all(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        // Moduli sharing a factor
+        assert_eq!(crt(3, 4, 1, 6), Some((7, 12)));
+        assert_eq!(crt(0, 4, 1, 6), None);
+        assert_eq!(crt_all(&[]), Some((0, 1)));
+
+        // Combined modulus close to the limit
+        let (m1, m2) = (3037000493, 3037000453);
+        let (a, m) = crt(m1 - 1, m1, m2 - 1, m2).unwrap();
+        assert_eq!(m, m1 * m2);
+        assert_eq!(a, m - 1);
+        assert_eq!(crt(0, i64::MAX, 1, 2), None);
+        assert_eq!(
+            crt(i64::MAX - 1, i64::MAX, i64::MAX - 1, i64::MAX),
+            Some((i64::MAX - 1, i64::MAX))
+        );
+    }
+}
@@ -0,0 +1,294 @@
+// Static information about every implemented puzzle
+use anyhow::{anyhow, bail, Result};
+
+#[derive(Debug)]
+pub struct Example {
+    pub input: &'static str,
+    // Expected answers, None when the example doesn't cover the part
+    pub part1: Option<&'static str>,
+    pub part2: Option<&'static str>,
+}
+
+#[derive(Debug)]
+pub struct Problem {
+    pub day: u8,
+    pub title: &'static str,
+    // Parts which are solved by running an Intcode program
+    pub intcode_parts: &'static [u8],
+    pub examples: &'static [Example],
+}
+
+impl Problem {
+    pub fn is_intcode(&self, part: u8) -> bool {
+        self.intcode_parts.contains(&part)
+    }
+}
+
+pub const PROBLEMS: &[Problem] = &[
+    Problem {
+        day: 1,
+        title: "The Tyranny of the Rocket Equation",
+        intcode_parts: &[],
+        examples: &[
+            Example {
+                input: "12",
+                part1: Some("2"),
+                part2: Some("2"),
+            },
+            Example {
+                input: "14",
+                part1: Some("2"),
+                part2: Some("2"),
+            },
+            Example {
+                input: "1969",
+                part1: Some("654"),
+                part2: Some("966"),
+            },
+            Example {
+                input: "100756",
+                part1: Some("33583"),
+                part2: Some("50346"),
+            },
+        ],
+    },
+    Problem {
+        day: 2,
+        title: "1202 Program Alarm",
+        intcode_parts: &[1, 2],
+        // Sample programs are too short to be patched with noun and verb
+        examples: &[],
+    },
+    Problem {
+        day: 3,
+        title: "Crossed Wires",
+        intcode_parts: &[],
+        examples: &[
+            Example {
+                input: "R8,U5,L5,D3\nU7,R6,D4,L4",
+                part1: Some("6"),
+                part2: Some("30"),
+            },
+            Example {
+                input: "R75,D30,R83,U83,L12,D49,R71,U7,L72\nU62,R66,U55,R34,D71,R55,D58,R83",
+                part1: Some("159"),
+                part2: Some("610"),
+            },
+            Example {
+                input: "R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51\nU98,R91,D20,R16,D67,R40,U7,R15,U6,R7",
+                part1: Some("135"),
+                part2: Some("410"),
+            },
+        ],
+    },
+    Problem {
+        day: 4,
+        title: "Secure Container",
+        intcode_parts: &[],
+        // Single password ranges, answer is 1 when password meets criteria
+        examples: &[
+            Example {
+                input: "111111-111111",
+                part1: Some("1"),
+                part2: Some("0"),
+            },
+            Example {
+                input: "223450-223450",
+                part1: Some("0"),
+                part2: Some("0"),
+            },
+            Example {
+                input: "123789-123789",
+                part1: Some("0"),
+                part2: Some("0"),
+            },
+            Example {
+                input: "112233-112233",
+                part1: Some("1"),
+                part2: Some("1"),
+            },
+            Example {
+                input: "123444-123444",
+                part1: Some("1"),
+                part2: Some("0"),
+            },
+            Example {
+                input: "111122-111122",
+                part1: Some("1"),
+                part2: Some("1"),
+            },
+        ],
+    },
+    Problem {
+        day: 6,
+        title: "Universal Orbit Map",
+        intcode_parts: &[],
+        examples: &[
+            Example {
+                input: "COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L",
+                part1: Some("42"),
+                part2: None,
+            },
+            Example {
+                input: "COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L\nK)YOU\nI)SAN",
+                part1: Some("54"),
+                part2: Some("4"),
+            },
+        ],
+    },
+];
+
+// Returns metadata for the day if it's implemented
+pub fn problem(day: u8) -> Option<&'static Problem> {
+    PROBLEMS.iter().find(|p| p.day == day)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Pass,
+    Fail { expected: String, actual: String },
+    Error(String),
+    // Example has no answer for the part
+    Skip,
+}
+
+// Solves example with public solution API and compares answers with expected ones
+pub fn check_example(day: u8, example: &Example) -> [Outcome; 2] {
+    let solution = match crate::solution(day) {
+        Some(solution) => solution,
+        None => {
+            let err = format!("Day {} is not implemented", day);
+            return [Outcome::Error(err.clone()), Outcome::Error(err)];
+        }
+    };
+
+    [
+        compare(example.part1, || solution.part1(example.input)),
+        compare(example.part2, || solution.part2(example.input)),
+    ]
+}
+
+// Answer is only computed when there is an expected one to compare it with
+pub fn compare(expected: Option<&str>, answer: impl FnOnce() -> Result<String>) -> Outcome {
+    let expected = match expected {
+        Some(expected) => expected,
+        None => return Outcome::Skip,
+    };
+
+    match answer() {
+        Ok(actual) if actual == expected => Outcome::Pass,
+        Ok(actual) => Outcome::Fail {
+            expected: expected.to_string(),
+            actual,
+        },
+        Err(err) => Outcome::Error(format!("{:#}", err)),
+    }
+}
+
+// Parses day given as 3, 03 or day3, fails when the day isn't implemented
+pub fn parse_day(s: &str) -> Result<u8> {
+    let day = parse_day_number(s)?;
+    if problem(day).is_none() {
+        let days: Vec<String> = PROBLEMS.iter().map(|p| p.day.to_string()).collect();
+        bail!(
+            "Day {} is not implemented, implemented days: {}",
+            day,
+            days.join(", ")
+        );
+    }
+
+    Ok(day)
+}
+
+// Like parse_day, but accepts any day of the calendar, implemented or not
+pub fn parse_day_number(s: &str) -> Result<u8> {
+    let s = s.trim();
+    let digits = s.strip_prefix("day").unwrap_or(s);
+    let day: u8 = digits
+        .parse()
+        .map_err(|_| anyhow!("Invalid day {:?}, expected a number like 3, 03 or day3", s))?;
+
+    if !(1..=25).contains(&day) {
+        bail!("Day {} is out of range, puzzles are numbered 1-25", day);
+    }
+    Ok(day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_problem_lookup() {
+        assert_eq!(problem(3).map(|p| p.title), Some("Crossed Wires"));
+        assert!(problem(2).unwrap().is_intcode(2));
+        assert!(!problem(1).unwrap().is_intcode(1));
+        assert!(problem(0).is_none());
+        assert!(problem(26).is_none());
+    }
+
+    #[test]
+    fn test_parse_day() {
+        for s in ["3", "03", "day3", "day03", " 3 "] {
+            assert_eq!(parse_day(s).unwrap(), 3, "day {:?}", s);
+        }
+
+        let err = parse_day("24").unwrap_err().to_string();
+        assert!(err.contains("not implemented"));
+        assert!(err.contains("1, 2, 3"));
+        assert!(parse_day("0")
+            .unwrap_err()
+            .to_string()
+            .contains("out of range"));
+        assert!(parse_day("26")
+            .unwrap_err()
+            .to_string()
+            .contains("out of range"));
+        assert!(parse_day("three").is_err());
+        assert!(parse_day("-1").is_err());
+
+        assert_eq!(parse_day_number("day24").unwrap(), 24);
+        assert!(parse_day_number("26")
+            .unwrap_err()
+            .to_string()
+            .contains("out of range"));
+    }
+
+    #[test]
+    fn test_examples() {
+        for problem in PROBLEMS {
+            for example in problem.examples {
+                for outcome in check_example(problem.day, example) {
+                    assert!(
+                        matches!(outcome, Outcome::Pass | Outcome::Skip),
+                        "day {} example {:?}: {:?}",
+                        problem.day,
+                        example.input,
+                        outcome
+                    );
+                }
+            }
+        }
+
+        let example = Example {
+            input: "12",
+            part1: Some("3"),
+            part2: None,
+        };
+        assert_eq!(
+            check_example(1, &example),
+            [
+                Outcome::Fail {
+                    expected: "3".to_string(),
+                    actual: "2".to_string()
+                },
+                Outcome::Skip
+            ]
+        );
+    }
+
+    #[test]
+    fn test_problems_sorted() {
+        assert!(PROBLEMS.windows(2).all(|w| w[0].day < w[1].day));
+    }
+}
@@ -0,0 +1,77 @@
+// Position tracking shared by input parsers, so errors point at the offending token
+use std::error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    // Both counted from 1, column is in characters
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, column: usize, message: impl Into<String>) -> ParseError {
+        ParseError {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    // Sets line of error found by parser which only sees a single line
+    pub fn with_line(self, line: usize) -> ParseError {
+        ParseError { line, ..self }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl error::Error for ParseError {}
+
+// Splits line by separator, yields trimmed tokens with columns they start at.
+// Empty tokens are kept, so parsers can report missing values.
+pub fn tokens<'a>(
+    line: &'a str,
+    separator: impl Fn(char) -> bool + 'a,
+) -> impl Iterator<Item = (usize, &'a str)> + 'a {
+    line.split(separator).map(move |token| {
+        // Token is a subslice of line, so its offset is the pointer difference
+        let offset = token.as_ptr() as usize - line.as_ptr() as usize;
+        let leading = token.len() - token.trim_start().len();
+        (column(line, offset + leading), token.trim())
+    })
+}
+
+// Converts byte offset in line into column counted from 1
+pub fn column(line: &str, offset: usize) -> usize {
+    line[..offset].chars().count() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens() {
+        let found: Vec<_> = tokens("R8, U5,,  L5", |c| c == ',').collect();
+        assert_eq!(found, [(1, "R8"), (5, "U5"), (8, ""), (11, "L5")]);
+
+        let found: Vec<_> = tokens("é,x", |c| c == ',').collect();
+        assert_eq!(found, [(1, "é"), (3, "x")]);
+    }
+
+    #[test]
+    fn test_display() {
+        let err = ParseError::new(1, 14, "unknown direction 'X'").with_line(2);
+        assert_eq!(err.to_string(), "line 2, column 14: unknown direction 'X'");
+    }
+}
@@ -0,0 +1,96 @@
+// Progress reporting of long running solvers
+use crate::cancel::CancellationToken;
+use anyhow::Result;
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// Shared by worker threads of parallel solvers
+pub trait Progress: Sync {
+    // Called by solver with number of processed items out of total
+    fn update(&self, done: u64, total: u64);
+
+    // Called by solver between units of work, solver stops with returned error
+    fn check(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+// Discards all updates
+pub struct NoProgress;
+
+impl Progress for NoProgress {
+    fn update(&self, _done: u64, _total: u64) {}
+}
+
+// Draws progress bar on stderr, redrawing only when percentage changes.
+// Nothing is drawn if stderr is not a terminal.
+pub struct ProgressBar {
+    enabled: bool,
+    cancel: Option<CancellationToken>,
+    // Last drawn percentage plus one, zero means bar wasn't drawn yet
+    last: AtomicU64,
+}
+
+const BAR_WIDTH: u64 = 40;
+
+impl ProgressBar {
+    pub fn new() -> ProgressBar {
+        ProgressBar {
+            enabled: io::stderr().is_terminal(),
+            cancel: None,
+            last: AtomicU64::new(0),
+        }
+    }
+
+    // Makes solvers stop once token is cancelled
+    pub fn cancellable(self, token: CancellationToken) -> ProgressBar {
+        ProgressBar {
+            cancel: Some(token),
+            ..self
+        }
+    }
+
+    // Erases bar so following output starts from clean line
+    pub fn finish(&self) {
+        if self.last.swap(0, Ordering::Relaxed) != 0 {
+            eprint!("\r{:width$}\r", "", width = BAR_WIDTH as usize + 8);
+        }
+    }
+}
+
+impl Default for ProgressBar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Progress for ProgressBar {
+    fn update(&self, done: u64, total: u64) {
+        if !self.enabled {
+            return;
+        }
+
+        let percent = (done * 100).checked_div(total).unwrap_or(100).min(100);
+        if self.last.swap(percent + 1, Ordering::Relaxed) == percent + 1 {
+            return;
+        }
+
+        let filled = (percent * BAR_WIDTH / 100) as usize;
+        let mut stderr = io::stderr().lock();
+        let _ = write!(
+            stderr,
+            "\r[{:<width$}] {:>3}%",
+            "#".repeat(filled),
+            percent,
+            width = BAR_WIDTH as usize
+        );
+        let _ = stderr.flush();
+    }
+
+    fn check(&self) -> Result<()> {
+        match &self.cancel {
+            Some(token) => Ok(token.check()?),
+            None => Ok(()),
+        }
+    }
+}
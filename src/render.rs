@@ -0,0 +1,109 @@
+// Common frame format shared by solvers which can show their state visually
+use anyhow::Result;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Style {
+    #[default]
+    Empty,
+    // Path of n-th object, e.g. wire of day 3
+    Trace(u8),
+    // Cell which is part of the answer
+    Highlight,
+    // Starting point, like the central port
+    Origin,
+}
+
+impl Style {
+    // Character used by the terminal renderer
+    pub fn glyph(&self) -> char {
+        match self {
+            Style::Empty => '.',
+            Style::Trace(n) => char::from_digit(*n as u32 % 9 + 1, 10).unwrap(),
+            Style::Highlight => 'X',
+            Style::Origin => 'o',
+        }
+    }
+}
+
+// Grid of styled cells, row 0 is drawn at the top
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    cells: Vec<Style>,
+}
+
+impl Frame {
+    pub fn new(width: usize, height: usize) -> Frame {
+        Frame {
+            width,
+            height,
+            cells: vec![Style::Empty; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<Style> {
+        self.index(x, y).map(|i| self.cells[i])
+    }
+
+    // Cells outside of the frame are ignored
+    pub fn set(&mut self, x: usize, y: usize, style: Style) {
+        if let Some(i) = self.index(x, y) {
+            self.cells[i] = style;
+        }
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[Style]> {
+        self.cells.chunks(self.width.max(1)).take(self.height)
+    }
+
+    fn index(&self, x: usize, y: usize) -> Option<usize> {
+        (x < self.width && y < self.height).then(|| y * self.width + x)
+    }
+}
+
+// Terminal rendering, one glyph per cell
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.rows() {
+            let line: String = row.iter().map(Style::glyph).collect();
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+// Implemented by solutions which can visualize how the answer is found.
+// Frames never exceed given size, large pictures are scaled down.
+pub trait Renderable: Send + Sync {
+    fn render(&self, input: &str, max_width: usize, max_height: usize) -> Result<Vec<Frame>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame() {
+        let mut frame = Frame::new(3, 2);
+        frame.set(0, 0, Style::Origin);
+        frame.set(2, 1, Style::Trace(0));
+        frame.set(1, 1, Style::Trace(1));
+        frame.set(3, 0, Style::Highlight);
+
+        assert_eq!(frame.get(0, 0), Some(Style::Origin));
+        assert_eq!(frame.get(1, 0), Some(Style::Empty));
+        assert_eq!(frame.get(3, 0), None);
+        assert_eq!(frame.rows().count(), 2);
+        assert_eq!(frame.to_string(), "o..\n.21\n");
+    }
+}
@@ -0,0 +1,198 @@
+// Interactive shell running solutions and Intcode programs without restarting the binary
+use anyhow::{anyhow, bail, Context, Result};
+use aoc_2019::computer::{IntcodeComputer, Memory, StepResult};
+use aoc_2019::input::{self, Input};
+#[cfg(feature = "alloc-stats")]
+use aoc_2019::{alloc_stats, report};
+use aoc_2019::{metadata, solution};
+use std::io::{self, BufRead, Write};
+use std::time::Instant;
+
+const HELP: &str = "\
+Commands:
+  run <day> [path] [part]   solve day, both parts by default
+  bench <day> [path]        solve day and show time and peak heap usage per part
+  list                      show implemented days
+  intcode load <path>       load Intcode program
+  intcode step [count]      execute next instruction(s)
+  intcode run               execute program until it halts
+  intcode input <value>...  queue values read by input instructions
+  intcode reset             restore program memory
+  intcode state             show instruction pointer, next instruction and memory around it
+  intcode disassemble       list memory as instructions
+  intcode diff              show memory cells changed since the program was loaded
+  peek <address> [count]    show Intcode memory
+  help                      show this message
+  quit                      leave the shell";
+
+#[derive(Default)]
+struct Session {
+    computer: Option<IntcodeComputer>,
+}
+
+pub fn run() -> Result<()> {
+    let mut session = Session::default();
+    let stdin = io::stdin();
+    let mut line = String::new();
+
+    loop {
+        print!("aoc> ");
+        io::stdout().flush()?;
+
+        line.clear();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            // End of input
+            println!();
+            break;
+        }
+
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            [] => continue,
+            ["quit"] | ["exit"] => break,
+            words => {
+                if let Err(err) = session.execute(words) {
+                    println!("Error: {:#}", err);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+impl Session {
+    fn execute(&mut self, words: &[&str]) -> Result<()> {
+        match words {
+            ["help"] => println!("{}", HELP),
+            ["list"] => {
+                for problem in metadata::PROBLEMS {
+                    println!("Day {:02}: {}", problem.day, problem.title);
+                }
+            }
+            ["run", day] => solve(day, None, None, false)?,
+            ["run", day, path] => solve(day, Some(path), None, false)?,
+            ["run", day, path, part] => solve(day, Some(path), Some(part), false)?,
+            ["bench", day] => solve(day, None, None, true)?,
+            ["bench", day, path] => solve(day, Some(path), None, true)?,
+            ["intcode", "load", path] => {
+                let program = Input::read(path)?;
+                let computer = IntcodeComputer::new(&program.normalized())?;
+                println!("Loaded {} values", computer.ram().len());
+                self.computer = Some(computer);
+            }
+            ["intcode", "step"] => self.step(1)?,
+            ["intcode", "step", count] => self.step(count.parse().context("Invalid count")?)?,
+            ["intcode", "run"] => self.step(usize::MAX)?,
+            ["intcode", "input", values @ ..] if !values.is_empty() => {
+                let values = values
+                    .iter()
+                    .map(|v| v.parse())
+                    .collect::<Result<Vec<i64>, _>>()
+                    .context("Invalid input value")?;
+                let computer = self.computer()?;
+                for value in values {
+                    computer.push_input(value);
+                }
+            }
+            ["intcode", "reset"] => self.computer()?.reset(),
+            ["intcode", "state"] => print!("{}", self.computer()?),
+            ["intcode", "disassemble"] => print!("{}", self.computer()?.disassemble()),
+            ["intcode", "diff"] => {
+                let changes = self.computer()?.changes();
+                if changes.is_empty() {
+                    println!("No cells changed");
+                } else {
+                    print!("{}", changes);
+                }
+            }
+            ["peek", address] => self.peek(address.parse().context("Invalid address")?, 1)?,
+            ["peek", address, count] => self.peek(
+                address.parse().context("Invalid address")?,
+                count.parse().context("Invalid count")?,
+            )?,
+            _ => bail!("Unknown command, type `help` to list commands"),
+        }
+
+        Ok(())
+    }
+
+    fn computer(&mut self) -> Result<&mut IntcodeComputer> {
+        self.computer
+            .as_mut()
+            .ok_or(anyhow!("No program loaded, use `intcode load <path>`"))
+    }
+
+    fn step(&mut self, count: usize) -> Result<()> {
+        let computer = self.computer()?;
+        let mut executed = 0;
+        while executed < count && !computer.is_halted() {
+            match computer.step()? {
+                Some(StepResult::Output(value)) => println!("output: {}", value),
+                Some(StepResult::NeedsInput) => {
+                    println!("waiting for input, use `intcode input <value>...`");
+                    break;
+                }
+                _ => {}
+            }
+            executed += 1;
+        }
+        println!(
+            "ip: {}, halted: {}, executed: {}",
+            computer.ip(),
+            computer.is_halted(),
+            executed
+        );
+        Ok(())
+    }
+
+    fn peek(&mut self, address: usize, count: usize) -> Result<()> {
+        let ram = self.computer()?.ram();
+        for address in address..address.saturating_add(count) {
+            println!("{:>6}: {}", address, ram.read(address)?);
+        }
+        Ok(())
+    }
+}
+
+fn solve(day: &str, path: Option<&str>, part: Option<&str>, timed: bool) -> Result<()> {
+    let day = metadata::parse_day(day)?;
+    let solution = solution(day).ok_or(anyhow!("Day {} is not implemented", day))?;
+    let path = path.map_or_else(|| input::default_path(day), str::to_string);
+    let input = Input::read_day(day, &path)?;
+    let input = input.normalized();
+
+    let parts: &[&str] = match part {
+        None => &["1", "2"],
+        Some("1") => &["1"],
+        Some("2") => &["2"],
+        Some(_) => bail!("Part must be 1 or 2"),
+    };
+
+    for &part in parts {
+        #[cfg(feature = "alloc-stats")]
+        let baseline = alloc_stats::start_heap_measurement();
+        let start = Instant::now();
+        let answer = if part == "1" {
+            solution.part1(&input)?
+        } else {
+            solution.part2(&input)?
+        };
+
+        if timed {
+            let elapsed = start.elapsed();
+            #[cfg(feature = "alloc-stats")]
+            let heap = format!(
+                ", peak heap {}",
+                report::format_bytes(alloc_stats::peak_heap(baseline))
+            );
+            #[cfg(not(feature = "alloc-stats"))]
+            let heap = "";
+            println!("answer {}: {} ({:?}{})", part, answer, elapsed, heap);
+        } else {
+            println!("answer {}: {}", part, answer);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,199 @@
+// Timing of repeated solver runs and plain text tables for console reports
+use anyhow::Result;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+// Summary of measured run times
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub runs: usize,
+    pub min: Duration,
+    pub mean: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    // None when nothing was measured
+    pub fn from_samples(samples: &[Duration]) -> Option<Stats> {
+        let min = *samples.iter().min()?;
+        let max = *samples.iter().max()?;
+        let total: Duration = samples.iter().sum();
+        Some(Stats {
+            runs: samples.len(),
+            min,
+            mean: total / samples.len() as u32,
+            max,
+        })
+    }
+}
+
+// Calls solver given number of times and measures every call, returns answer of the
+// last one. The first error stops measurement, so broken solvers aren't timed.
+pub fn measure(runs: usize, mut solve: impl FnMut() -> Result<String>) -> Result<(String, Stats)> {
+    let mut samples = Vec::with_capacity(runs);
+    let mut answer = String::new();
+    for _ in 0..runs.max(1) {
+        let start = Instant::now();
+        answer = solve()?;
+        samples.push(start.elapsed());
+    }
+
+    let stats = Stats::from_samples(&samples).expect("solver runs at least once");
+    Ok((answer, stats))
+}
+
+// Formats duration with unit chosen by its magnitude, like 12.5 µs or 3.141 ms
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs_f64();
+    if secs < 1e-3 {
+        format!("{:.1} µs", secs * 1e6)
+    } else if secs < 1.0 {
+        format!("{:.3} ms", secs * 1e3)
+    } else {
+        format!("{:.3} s", secs)
+    }
+}
+
+// Formats byte count with binary unit, like 512 B or 1.5 MiB
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+// Table with columns aligned to the widest cell, the last column isn't padded
+pub struct Table {
+    header: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new<S: ToString>(header: impl IntoIterator<Item = S>) -> Table {
+        Table {
+            header: header.into_iter().map(|s| s.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    // Row is cut or filled with empty cells to the number of columns in header
+    pub fn push<S: ToString>(&mut self, row: impl IntoIterator<Item = S>) {
+        let mut row: Vec<String> = row.into_iter().map(|s| s.to_string()).collect();
+        row.resize(self.header.len(), String::new());
+        self.rows.push(row);
+    }
+
+    pub fn rows(&self) -> &[Vec<String>] {
+        &self.rows
+    }
+}
+
+impl fmt::Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let all = || [&self.header].into_iter().chain(&self.rows);
+        let widths: Vec<usize> = (0..self.header.len())
+            .map(|column| {
+                all()
+                    .map(|row| row[column].chars().count())
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        for row in all() {
+            let Some((last, cells)) = row.split_last() else {
+                continue;
+            };
+            for (cell, width) in cells.iter().zip(&widths) {
+                write!(f, "{:<width$}  ", cell)?;
+            }
+            writeln!(f, "{}", last)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::bail;
+
+    #[test]
+    fn test_stats() {
+        let ms = Duration::from_millis;
+        let stats = Stats::from_samples(&[ms(4), ms(1), ms(7)]).unwrap();
+        assert_eq!(
+            stats,
+            Stats {
+                runs: 3,
+                min: ms(1),
+                mean: ms(4),
+                max: ms(7),
+            }
+        );
+        assert_eq!(Stats::from_samples(&[]), None);
+    }
+
+    #[test]
+    fn test_measure() {
+        let mut calls = 0;
+        let (answer, stats) = measure(3, || {
+            calls += 1;
+            Ok(calls.to_string())
+        })
+        .unwrap();
+        assert_eq!(answer, "3");
+        assert_eq!(stats.runs, 3);
+        assert!(stats.min <= stats.mean && stats.mean <= stats.max);
+
+        let mut calls = 0;
+        assert!(measure(5, || {
+            calls += 1;
+            bail!("broken")
+        })
+        .is_err());
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_nanos(12_480)), "12.5 µs");
+        assert_eq!(format_duration(Duration::from_micros(3141)), "3.141 ms");
+        assert_eq!(format_duration(Duration::from_millis(2500)), "2.500 s");
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 << 20), "3.0 MiB");
+        assert_eq!(format_bytes(5 << 40), "5120.0 GiB");
+    }
+
+    #[test]
+    fn test_table() {
+        let mut table = Table::new(["Input", "Part 1", "Part 2"]);
+        table.push(["a.txt", "1", "2"]);
+        table.push(["longer.txt", "12345678", "µs"]);
+        assert_eq!(
+            table.to_string(),
+            "\
+Input       Part 1    Part 2
+a.txt       1         2
+longer.txt  12345678  µs
+"
+        );
+
+        table.push(["short.txt"]);
+        assert_eq!(table.rows()[2], ["short.txt", "", ""]);
+    }
+}
@@ -0,0 +1,188 @@
+// Brute force over list of candidates using the global thread pool. Candidates are
+// split into chunks which are searched one after another, items of a chunk are
+// checked in parallel. Progress is reported and cancellation checked between chunks.
+use crate::progress::Progress;
+use anyhow::Result;
+use rayon::prelude::*;
+use std::ops::RangeInclusive;
+
+// Candidates are split into this many chunks
+const CHUNKS: usize = 100;
+
+// Returns result of the first item, in list order, for which predicate returns Some.
+// Search stops at the first error the same way, so results don't depend on threads.
+pub fn parallel_brute_force<T, R>(
+    items: &[T],
+    progress: &dyn Progress,
+    predicate: impl Fn(&T) -> Result<Option<R>> + Sync,
+) -> Result<Option<R>>
+where
+    T: Sync,
+    R: Send,
+{
+    parallel_brute_force_with(items, progress, || (), |_, item| predicate(item))
+}
+
+// Like parallel_brute_force, but every worker thread gets state created by init,
+// e.g. own Intcode computer, which is reused for items checked by that thread
+pub fn parallel_brute_force_with<T, S, R>(
+    items: &[T],
+    progress: &dyn Progress,
+    init: impl Fn() -> S + Sync,
+    predicate: impl Fn(&mut S, &T) -> Result<Option<R>> + Sync,
+) -> Result<Option<R>>
+where
+    T: Sync,
+    R: Send,
+{
+    let total = items.len() as u64;
+    let mut done = 0;
+    for chunk in items.chunks(chunk_size(items.len())) {
+        progress.check()?;
+        let found = chunk
+            .par_iter()
+            .map_init(&init, |state, item| predicate(state, item).transpose())
+            .find_map_first(|result| result);
+        if let Some(result) = found {
+            return result.map(Some);
+        }
+
+        done += chunk.len() as u64;
+        progress.update(done, total);
+    }
+
+    Ok(None)
+}
+
+// Counts items matching predicate
+pub fn parallel_count<T: Sync>(
+    items: &[T],
+    progress: &dyn Progress,
+    predicate: impl Fn(&T) -> bool + Sync,
+) -> Result<usize> {
+    let total = items.len() as u64;
+    let mut done = 0;
+    let mut count = 0;
+    for chunk in items.chunks(chunk_size(items.len())) {
+        progress.check()?;
+        count += chunk.par_iter().filter(|item| predicate(item)).count();
+
+        done += chunk.len() as u64;
+        progress.update(done, total);
+    }
+
+    Ok(count)
+}
+
+// Like parallel_count, but over range of numbers, which is split into chunks
+// without being collected
+pub fn parallel_count_range(
+    range: RangeInclusive<u32>,
+    progress: &dyn Progress,
+    predicate: impl Fn(u32) -> bool + Sync,
+) -> Result<usize> {
+    if range.is_empty() {
+        return Ok(0);
+    }
+    let (start, end) = range.into_inner();
+    let total = (end - start) as u64 + 1;
+    let size = total.div_ceil(CHUNKS as u64).max(1);
+    let mut done = 0;
+    let mut count = 0;
+    while done < total {
+        progress.check()?;
+        let first = start + done as u32;
+        let last = first + (size.min(total - done) - 1) as u32;
+        count += (first..=last)
+            .into_par_iter()
+            .filter(|&n| predicate(n))
+            .count();
+
+        done += (last - first) as u64 + 1;
+        progress.update(done, total);
+    }
+
+    Ok(count)
+}
+
+fn chunk_size(len: usize) -> usize {
+    len.div_ceil(CHUNKS).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cancel::{CancellationToken, Cancelled};
+    use crate::progress::{NoProgress, ProgressBar};
+    use anyhow::bail;
+
+    #[test]
+    fn test_parallel_brute_force() {
+        let items: Vec<u32> = (0..10_000).collect();
+        let found = parallel_brute_force(&items, &NoProgress, |&i| {
+            Ok((i % 1000 == 999).then_some(i * 2))
+        });
+        assert_eq!(found.unwrap(), Some(1998));
+
+        let found = parallel_brute_force(&items, &NoProgress, |_| Ok(None::<u32>));
+        assert_eq!(found.unwrap(), None);
+
+        // Error comes before the match in list order
+        let found = parallel_brute_force(&items, &NoProgress, |&i| match i {
+            500 => bail!("failed at {}", i),
+            600 => Ok(Some(i)),
+            _ => Ok(None),
+        });
+        assert_eq!(found.unwrap_err().to_string(), "failed at 500");
+    }
+
+    #[test]
+    fn test_parallel_brute_force_with_state() {
+        let items: Vec<u32> = (0..1000).collect();
+        let found =
+            parallel_brute_force_with(&items, &NoProgress, Vec::new, |seen: &mut Vec<u32>, &i| {
+                seen.push(i);
+                Ok((i == 700).then_some(i))
+            });
+        assert_eq!(found.unwrap(), Some(700));
+    }
+
+    #[test]
+    fn test_parallel_count() {
+        let items: Vec<u32> = (0..10_000).collect();
+        assert_eq!(
+            parallel_count(&items, &NoProgress, |i| i % 3 == 0).unwrap(),
+            3334
+        );
+        assert_eq!(
+            parallel_count(&[] as &[u32], &NoProgress, |_| true).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_parallel_count_range() {
+        let count = |range| parallel_count_range(range, &NoProgress, |i| i % 3 == 0).unwrap();
+        assert_eq!(count(0..=9_999), 3334);
+        assert_eq!(count(5..=5), 0);
+        assert_eq!(count(6..=6), 1);
+        assert_eq!(count(RangeInclusive::new(7, 6)), 0);
+        // Chunks never step past the end of the range
+        assert_eq!(count(u32::MAX - 10..=u32::MAX), 4);
+    }
+
+    #[test]
+    fn test_cancelled() {
+        let token = CancellationToken::new();
+        token.cancel();
+        let progress = ProgressBar::new().cancellable(token);
+        let items = [1, 2, 3];
+
+        let err = parallel_brute_force(&items, &progress, |_| Ok(Some(1))).unwrap_err();
+        assert!(err.is::<Cancelled>());
+        let err = parallel_count(&items, &progress, |_| true).unwrap_err();
+        assert!(err.is::<Cancelled>());
+        let err = parallel_count_range(1..=3, &progress, |_| true).unwrap_err();
+        assert!(err.is::<Cancelled>());
+    }
+}
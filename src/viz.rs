@@ -0,0 +1,271 @@
+// Terminal animation of frames produced by Renderable solutions.
+// Only cells which changed since the previous frame are redrawn.
+//
+// Keys: space pauses and resumes, n or right arrow shows next frame, q or Esc quits.
+use crate::render::{Frame, Style};
+use anyhow::Result;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::{cursor, style, terminal, ExecutableCommand, QueueableCommand};
+use std::io::{self, IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+// Rows taken by status line below the frame
+const STATUS_LINES: u16 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    TogglePause,
+    Step,
+    Quit,
+}
+
+// Playback position, separated from the terminal so it can be tested
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Playback {
+    pub index: usize,
+    pub len: usize,
+    pub paused: bool,
+    pub finished: bool,
+}
+
+impl Playback {
+    pub fn new(len: usize) -> Playback {
+        Playback {
+            index: 0,
+            len,
+            paused: false,
+            finished: len == 0,
+        }
+    }
+
+    // Called when frame interval passes
+    pub fn tick(&mut self) {
+        if !self.paused {
+            self.advance();
+        }
+    }
+
+    pub fn key(&mut self, key: Key) {
+        match key {
+            Key::TogglePause => self.paused = !self.paused,
+            Key::Step => {
+                self.paused = true;
+                self.advance();
+            }
+            Key::Quit => self.finished = true,
+        }
+    }
+
+    // Animation stays at the last frame until it's closed
+    fn advance(&mut self) {
+        if self.index + 1 < self.len {
+            self.index += 1;
+        } else {
+            self.paused = true;
+        }
+    }
+}
+
+// Returns cells which have to be redrawn to turn previous frame into next one,
+// None when the whole screen has to be redrawn
+pub fn changed_cells(previous: Option<&Frame>, next: &Frame) -> Option<Vec<(usize, usize, Style)>> {
+    let previous = previous?;
+    if (previous.width(), previous.height()) != (next.width(), next.height()) {
+        return None;
+    }
+
+    let mut cells = Vec::new();
+    for (y, (old, new)) in previous.rows().zip(next.rows()).enumerate() {
+        for (x, (old, new)) in old.iter().zip(new).enumerate() {
+            if old != new {
+                cells.push((x, y, *new));
+            }
+        }
+    }
+    Some(cells)
+}
+
+// Size frames should be rendered with to fit into terminal
+pub fn viewport() -> (usize, usize) {
+    let (width, height) = terminal::size().unwrap_or((80, 24));
+    (
+        width as usize,
+        height.saturating_sub(STATUS_LINES).max(1) as usize,
+    )
+}
+
+pub struct Player {
+    interval: Duration,
+}
+
+impl Player {
+    pub fn new(fps: f64) -> Player {
+        Player {
+            interval: Duration::from_secs_f64(1.0 / fps.max(0.1)),
+        }
+    }
+
+    // Plays frames interactively, just prints them when output is not a terminal
+    pub fn play(&self, frames: &[Frame]) -> Result<()> {
+        if !io::stdout().is_terminal() {
+            for frame in frames {
+                println!("{}", frame);
+            }
+            return Ok(());
+        }
+
+        let _screen = Screen::enter()?;
+        let mut out = io::BufWriter::new(io::stdout());
+        let mut playback = Playback::new(frames.len());
+        let mut shown: Option<usize> = None;
+
+        while !playback.finished {
+            if shown != Some(playback.index) {
+                let previous = shown.map(|i| &frames[i]);
+                draw(&mut out, previous, &frames[playback.index])?;
+                shown = Some(playback.index);
+            }
+            draw_status(&mut out, &playback, frames[playback.index].height())?;
+            out.flush()?;
+
+            let next_tick = Instant::now() + self.interval;
+            match wait_key(next_tick, playback.paused)? {
+                Some(key) => playback.key(key),
+                None => playback.tick(),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Waits for a key until the deadline, or without limit when paused
+fn wait_key(deadline: Instant, paused: bool) -> Result<Option<Key>> {
+    loop {
+        let timeout = if paused {
+            Duration::from_secs(3600)
+        } else {
+            deadline.saturating_duration_since(Instant::now())
+        };
+        if !event::poll(timeout)? {
+            if paused {
+                continue;
+            }
+            return Ok(None);
+        }
+
+        if let Event::Key(key) = event::read()? {
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char(' ') => return Ok(Some(Key::TogglePause)),
+                KeyCode::Char('n') | KeyCode::Right => return Ok(Some(Key::Step)),
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(Some(Key::Quit)),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn draw(out: &mut impl Write, previous: Option<&Frame>, next: &Frame) -> Result<()> {
+    match changed_cells(previous, next) {
+        Some(cells) => {
+            for (x, y, style) in cells {
+                out.queue(cursor::MoveTo(x as u16, y as u16))?;
+                out.queue(style::Print(style.glyph()))?;
+            }
+        }
+        None => {
+            out.queue(terminal::Clear(terminal::ClearType::All))?;
+            for (y, row) in next.rows().enumerate() {
+                let line: String = row.iter().map(Style::glyph).collect();
+                out.queue(cursor::MoveTo(0, y as u16))?;
+                out.queue(style::Print(line))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn draw_status(out: &mut impl Write, playback: &Playback, row: usize) -> Result<()> {
+    let state = if playback.paused { "paused" } else { "playing" };
+    out.queue(cursor::MoveTo(0, row as u16))?;
+    out.queue(terminal::Clear(terminal::ClearType::CurrentLine))?;
+    out.queue(style::Print(format!(
+        "frame {}/{} {}  space: pause  n: step  q: quit",
+        playback.index + 1,
+        playback.len,
+        state
+    )))?;
+    Ok(())
+}
+
+// Raw mode and alternate screen, terminal is restored when dropped
+struct Screen;
+
+impl Screen {
+    fn enter() -> Result<Screen> {
+        terminal::enable_raw_mode()?;
+        let mut stdout = io::stdout();
+        stdout.execute(terminal::EnterAlternateScreen)?;
+        stdout.execute(cursor::Hide)?;
+        Ok(Screen)
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let mut stdout = io::stdout();
+        let _ = stdout.execute(cursor::Show);
+        let _ = stdout.execute(terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_playback() {
+        let mut playback = Playback::new(3);
+        playback.tick();
+        assert_eq!(playback.index, 1);
+
+        playback.key(Key::TogglePause);
+        playback.tick();
+        assert_eq!(playback.index, 1);
+        playback.key(Key::Step);
+        assert_eq!(playback.index, 2);
+        assert!(playback.paused);
+
+        // Stays at the last frame
+        playback.key(Key::TogglePause);
+        playback.tick();
+        assert_eq!(playback.index, 2);
+        assert!(playback.paused);
+        assert!(!playback.finished);
+
+        playback.key(Key::Quit);
+        assert!(playback.finished);
+        assert!(Playback::new(0).finished);
+    }
+
+    #[test]
+    fn test_changed_cells() {
+        let mut previous = Frame::new(3, 2);
+        previous.set(0, 0, Style::Origin);
+        let mut next = previous.clone();
+        next.set(2, 1, Style::Trace(0));
+        next.set(0, 0, Style::Highlight);
+
+        assert_eq!(changed_cells(None, &next), None);
+        assert_eq!(
+            changed_cells(Some(&previous), &next),
+            Some(vec![(0, 0, Style::Highlight), (2, 1, Style::Trace(0))])
+        );
+        assert_eq!(changed_cells(Some(&next), &next), Some(vec![]));
+        assert_eq!(changed_cells(Some(&Frame::new(2, 2)), &next), None);
+    }
+}
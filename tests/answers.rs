@@ -0,0 +1,47 @@
+// Real puzzle answers of every implemented day, solved through the registry
+// with inputs from data directory
+use aoc_2019::answers::Answers;
+use aoc_2019::input::{self, Input};
+use aoc_2019::{metadata, solution, SOLUTIONS};
+
+const ANSWERS: &[(u8, &str, &str)] = &[
+    (1, "3576689", "5362136"),
+    (2, "5305097", "4925"),
+    (3, "1337", "65356"),
+    (4, "1650", "1129"),
+    (6, "59494", "67"),
+];
+
+#[test]
+fn test_answers() {
+    for &(day, part1, part2) in ANSWERS {
+        // Path is absolute, so it doesn't depend on directory tests run in
+        let path = input::path_in(concat!(env!("CARGO_MANIFEST_DIR"), "/data"), day);
+        let input = Input::read(&path).unwrap();
+        let input = input.normalized();
+        let solution = solution(day).unwrap();
+
+        assert_eq!(solution.part1(&input).unwrap(), part1, "day {} part 1", day);
+        assert_eq!(solution.part2(&input).unwrap(), part2, "day {} part 2", day);
+    }
+}
+
+#[test]
+fn test_registry() {
+    let days: Vec<u8> = SOLUTIONS.iter().map(|&(day, _)| day).collect();
+    let answered: Vec<u8> = ANSWERS.iter().map(|&(day, _, _)| day).collect();
+    let described: Vec<u8> = metadata::PROBLEMS.iter().map(|p| p.day).collect();
+    assert_eq!(days, answered);
+    assert_eq!(days, described);
+    assert!(solution(25).is_none());
+}
+
+#[test]
+fn test_answers_file() {
+    let answers = Answers::read(concat!(env!("CARGO_MANIFEST_DIR"), "/answers.toml")).unwrap();
+    for &(day, part1, part2) in ANSWERS {
+        assert_eq!(answers.get(day, 1), Some(part1), "day {} part 1", day);
+        assert_eq!(answers.get(day, 2), Some(part2), "day {} part 2", day);
+    }
+    assert_eq!(answers.days().count(), ANSWERS.len());
+}
@@ -0,0 +1,59 @@
+// Opt-in check that every implemented day stays within time budget:
+//
+// cargo test --release --test budget -- --ignored
+//
+// Budgets in milliseconds can be changed with AOC_DAY_BUDGET_MS and AOC_TOTAL_BUDGET_MS.
+use aoc_2019::input::Input;
+use aoc_2019::{metadata, solution};
+use std::env;
+use std::time::{Duration, Instant};
+
+const DAY_BUDGET_MS: u64 = 1000;
+const TOTAL_BUDGET_MS: u64 = 15000;
+
+fn budget(var: &str, default: u64) -> Duration {
+    let ms = env::var(var)
+        .ok()
+        .map(|v| v.parse().expect("budget should be number of milliseconds"))
+        .unwrap_or(default);
+    Duration::from_millis(ms)
+}
+
+#[test]
+#[ignore]
+fn test_performance_budget() {
+    let day_budget = budget("AOC_DAY_BUDGET_MS", DAY_BUDGET_MS);
+    let total_budget = budget("AOC_TOTAL_BUDGET_MS", TOTAL_BUDGET_MS);
+
+    let mut total = Duration::ZERO;
+    let mut slow_days = Vec::new();
+    for problem in metadata::PROBLEMS {
+        let path = format!("data/input{:02}.txt", problem.day);
+        let input = Input::read(&path).unwrap();
+        let input = input.normalized();
+        let solution = solution(problem.day).unwrap();
+
+        let start = Instant::now();
+        solution.part1(&input).unwrap();
+        solution.part2(&input).unwrap();
+        let elapsed = start.elapsed();
+
+        total += elapsed;
+        if elapsed > day_budget {
+            slow_days.push(format!("day {:02}: {:?}", problem.day, elapsed));
+        }
+    }
+
+    assert!(
+        slow_days.is_empty(),
+        "days over {:?} budget: {}",
+        day_budget,
+        slow_days.join(", ")
+    );
+    assert!(
+        total <= total_budget,
+        "total {:?} is over {:?} budget",
+        total,
+        total_budget
+    );
+}
@@ -0,0 +1,189 @@
+// Snapshots of console output, review changes with `cargo insta review`
+use aoc_2019::{metadata, solution};
+use std::process::Command;
+
+// Runs the binary from the crate root so default input paths are stable
+fn run(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_aoc-2019"))
+        .args(args)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .env_remove("AOC_DAY")
+        .env_remove("AOC_INPUT_DIR")
+        .output()
+        .expect("binary should start");
+    assert!(output.status.success(), "aoc-2019 {:?} failed", args);
+
+    String::from_utf8(output.stdout).expect("output should be UTF-8")
+}
+
+#[test]
+fn test_solve_output() {
+    for problem in metadata::PROBLEMS {
+        let day = problem.day.to_string();
+        insta::assert_snapshot!(format!("day{:02}", problem.day), run(&[&day]));
+    }
+}
+
+#[test]
+fn test_dry_run_output() {
+    for problem in metadata::PROBLEMS {
+        let day = problem.day.to_string();
+        insta::assert_snapshot!(
+            format!("day{:02}_dry_run", problem.day),
+            run(&[&day, "--dry-run"])
+        );
+    }
+}
+
+#[test]
+fn test_list_output() {
+    insta::assert_snapshot!(run(&["list"]));
+}
+
+#[test]
+fn test_verify_examples_output() {
+    insta::assert_snapshot!(run(&["verify-examples"]));
+}
+
+#[test]
+fn test_rendered_frames() {
+    for problem in metadata::PROBLEMS {
+        let renderable = match solution(problem.day).and_then(|s| s.renderable()) {
+            Some(renderable) => renderable,
+            None => continue,
+        };
+
+        for (i, example) in problem.examples.iter().enumerate() {
+            let frames = renderable.render(example.input, 80, 40).unwrap();
+            let frame = frames.last().expect("at least one frame is rendered");
+            insta::assert_snapshot!(
+                format!("day{:02}_example{}_render", problem.day, i + 1),
+                frame.to_string()
+            );
+        }
+    }
+}
+
+#[test]
+fn test_batch_output() {
+    insta::assert_snapshot!(run(&["1", "data/input01.txt", "data/input0[1].txt"]));
+}
+
+#[test]
+fn test_range_output() {
+    let output = run(&["4", "--range", "111110-111125"]);
+    assert_eq!(output, run(&["4", "111110", "111125"]));
+    insta::assert_snapshot!(output);
+}
+
+#[test]
+fn test_run_all_output() {
+    insta::assert_snapshot!(run(&["run", "--all", "--part", "1"]));
+}
+
+#[test]
+fn test_part_selection() {
+    let output = run(&["run", "-d", "3", "--part", "2", "-i", "data/input03.txt"]);
+    assert_eq!(
+        output,
+        "Day: 3\nFilename: data/input03.txt\nanswer 2: 65356\n"
+    );
+}
+
+#[test]
+fn test_bench_output() {
+    // Timings change between runs, so only the layout is checked
+    let output = run(&["bench", "-d", "1", "-n", "2"]);
+    let rows: Vec<Vec<&str>> = output
+        .lines()
+        .map(|line| {
+            line.split("  ")
+                .map(str::trim)
+                .filter(|cell| !cell.is_empty())
+                .collect()
+        })
+        .collect();
+    assert_eq!(rows.len(), 3);
+    let mut header = vec!["Day", "Part", "Runs", "Min", "Mean", "Max"];
+    if cfg!(feature = "alloc-stats") {
+        header.push("Peak heap");
+    }
+    assert_eq!(rows[0], header);
+    for (row, part) in rows[1..].iter().zip(["1", "2"]) {
+        assert_eq!(row[..3], ["01", part, "2"]);
+        assert!(row[3..6].iter().all(|cell| cell.ends_with('s')));
+        if cfg!(feature = "alloc-stats") {
+            assert!(row[6].ends_with('B'));
+        }
+    }
+}
+
+#[test]
+fn test_intcode_run_output() {
+    let dir = std::env::temp_dir();
+    let path = dir.join(format!("aoc-{}-intcode.txt", std::process::id()));
+    std::fs::write(&path, "3,0,3,1,4,0,4,1,99").unwrap();
+    let output = run(&["intcode", "run", path.to_str().unwrap(), "--input", "-5,7"]);
+    assert_eq!(output, "-5,7,3,1,4,0,4,1,99\nOutput: -5,7\n");
+
+    // Echoes three characters, then outputs a value outside ASCII
+    std::fs::write(&path, "3,15,4,15,3,15,4,15,3,15,4,15,104,1000,99").unwrap();
+    let output = run(&["intcode", "run", path.to_str().unwrap(), "--ascii", "hi"]);
+    std::fs::remove_file(&path).unwrap();
+    let (memory, output) = output.split_once('\n').unwrap();
+    assert!(memory.ends_with(",99,10"));
+    assert_eq!(output, "Output:\nhi\n1000\n");
+}
+
+#[test]
+fn test_verify_output() {
+    insta::assert_snapshot!(run(&["verify"]));
+
+    // Recorded answers of days without a solution are missing, not failures
+    let path = std::env::temp_dir().join(format!("aoc-{}-answers.toml", std::process::id()));
+    std::fs::write(
+        &path,
+        "[day01]\npart1 = 3576689\n\n[day25]\npart1 = \"1\"\n",
+    )
+    .unwrap();
+    let output = run(&["verify", "--answers", path.to_str().unwrap()]);
+    std::fs::remove_file(&path).unwrap();
+    assert!(output.contains("25   missing  missing\n"));
+    assert!(output.contains("day 25: not implemented\n"));
+    assert!(output.ends_with("1 passed, 0 failed, 11 missing\n"));
+}
+
+// Runs the binary expecting it to fail, returns exit status and stderr
+fn run_failing(args: &[&str]) -> (i32, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_aoc-2019"))
+        .args(args)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .env_remove("AOC_DAY")
+        .env_remove("AOC_INPUT_DIR")
+        .output()
+        .expect("binary should start");
+    assert!(!output.status.success(), "aoc-2019 {:?} succeeded", args);
+
+    let stderr = String::from_utf8(output.stderr).expect("output should be UTF-8");
+    (output.status.code().unwrap_or_default(), stderr)
+}
+
+#[test]
+fn test_error_diagnostics() {
+    let path = std::env::temp_dir().join(format!("aoc-{}-masses.txt", std::process::id()));
+    std::fs::write(&path, "12\n1x4\n").unwrap();
+    let path = path.to_str().unwrap();
+    let (status, stderr) = run_failing(&["1", path]);
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(status, 2);
+    assert!(stderr.contains("line 2, column 1: invalid mass \"1x4\""));
+    assert!(stderr.contains(&format!("  --> {}:2:1\n", path)));
+
+    let (status, stderr) = run_failing(&["run", "-d", "3", "--range", "1-2"]);
+    assert_eq!(status, 1);
+    assert!(stderr.starts_with("error: --range is only accepted by day 4\n"));
+
+    let (status, stderr) = run_failing(&["run"]);
+    assert_eq!(status, 1);
+    assert!(stderr.contains("Day is not specified"));
+}